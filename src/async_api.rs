@@ -0,0 +1,50 @@
+use crate::*;
+use rule::Rule;
+use solver::{RunOutcome, SolveConfig};
+
+use anyhow::{Context, Result};
+
+// Solves the rules like solver::run on the blocking thread pool of the tokio runtime, so awaiting
+// the outcome doesn't block the other tasks of the runtime.
+pub async fn resolve_async(
+    rules: Vec<Rule>,
+    facts: String,
+    queries: String,
+    config: SolveConfig,
+) -> Result<RunOutcome> {
+    tokio::task::spawn_blocking(move || solver::run(&rules, &facts, &queries, &config))
+        .await
+        .context("Solving task failed")
+}
+
+#[cfg(test)]
+mod tests_resolve_async {
+    use super::*;
+    use solver::Query;
+    use truth::Truth;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn current_thread() -> Result<()> {
+        let rules = ["A=>B", "B=>C"]
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<Vec<Rule>>>()?;
+        let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+        let outcome = runtime.block_on(resolve_async(
+            rules,
+            "A".to_string(),
+            "CD".to_string(),
+            SolveConfig::default(),
+        ))?;
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('C'), Truth::True),
+                (Query::from('D'), Truth::False)
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+// CancelFlag is shared with e.g. a SIGINT handler to stop building truth tables and solving early.
+// Clones share the flag, so cancelling one cancels all of them.
+#[derive(Debug, Clone, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// Flags are equal when they are clones of each other.
+impl PartialEq for CancelFlag {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancelFlag {}
+
+#[cfg(test)]
+mod tests_cancel_flag {
+    use super::*;
+
+    #[test]
+    fn shared_by_clones() {
+        let flag = CancelFlag::new();
+        let clone = flag.clone();
+        assert!(!flag.is_cancelled());
+        clone.cancel();
+        assert!(flag.is_cancelled());
+        assert_eq!(flag, clone);
+        assert_ne!(flag, CancelFlag::new());
+    }
+}
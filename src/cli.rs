@@ -0,0 +1,452 @@
+use crate::*;
+use operator::OperatorSet;
+use parser::{CsvOptions, ImplicatorStyle, TableFormat};
+use solver::{Mode, OrPolicy};
+use truth::BoolFormat;
+
+use anyhow::{anyhow, Context, Result};
+use std::{num::NonZeroUsize, path::PathBuf, str::FromStr};
+
+// Options holds the parsed command line arguments, excluding the program name.
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    pub input_file: PathBuf,
+    pub proof: bool,
+    pub why: bool,
+    pub json: bool,
+    pub consistency: bool,
+    pub relevant: bool,
+    pub shuffle_seed: Option<u64>,
+    pub bool_format: BoolFormat,
+    pub table_format: TableFormat,
+    pub tokens: Option<String>,
+    pub eval: bool,
+    pub allow_unset: bool,
+    pub quiet: bool,
+    pub no_tables: bool,
+    pub fmt: bool,
+    pub fmt_write: bool,
+    pub count_models: bool,
+    pub dimacs: bool,
+    pub conclusions: bool,
+    pub closure: bool,
+    pub as_equivalence: bool,
+    pub queries: Vec<String>,
+    pub facts_file: Option<PathBuf>,
+    pub checkpoint: Option<PathBuf>,
+    #[cfg(feature = "profiling")]
+    pub profile: Option<PathBuf>,
+    pub implicator_style: ImplicatorStyle,
+    pub dialect: OperatorSet,
+    pub abduce: Option<char>,
+    pub mode: Mode,
+    pub or_policy: OrPolicy,
+    pub max_depth: Option<usize>,
+    pub max_threads: Option<usize>,
+    pub explain_contradiction: bool,
+    pub explain_undetermined: bool,
+    pub stats: bool,
+    pub coverage: bool,
+    pub verbose: bool,
+    pub watch: bool,
+    pub exit_code: bool,
+    pub expand_bicond: bool,
+    pub sort_rules: bool,
+    pub yes: bool,
+    pub strict: bool,
+}
+
+// Parses the value following an option.
+fn value<T: FromStr>(option: &str, value: Option<&String>) -> Result<T> {
+    let value = value.context(format!("Missing value for option: '{}'", option))?;
+    value
+        .parse()
+        .map_err(|_| anyhow!("Invalid value for option '{}': '{}'", option, value))
+}
+
+impl Options {
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let mut options = Options::default();
+        let mut input_file = None;
+        let mut csv = CsvOptions::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--quiet" => options.quiet = true,
+                "--no-tables" => options.no_tables = true,
+                "--proof" => options.proof = true,
+                "--why" => options.why = true,
+                "--json" => options.json = true,
+                "--consistency" => options.consistency = true,
+                "--relevant" => options.relevant = true,
+                "--stats" => options.stats = true,
+                "--coverage" => options.coverage = true,
+                "--verbose" => options.verbose = true,
+                #[cfg(not(feature = "wasm"))]
+                "--watch" => options.watch = true,
+                #[cfg(feature = "wasm")]
+                "--watch" => {
+                    return Err(anyhow!(
+                        "Option '--watch' is unavailable: built with the wasm feature"
+                    ))
+                }
+                "--exit-code" => options.exit_code = true,
+                "--explain-contradiction" => options.explain_contradiction = true,
+                "--explain-undetermined" => options.explain_undetermined = true,
+                "--fmt" => options.fmt = true,
+                "--fmt-write" => options.fmt_write = true,
+                "--expand-bicond" => options.expand_bicond = true,
+                "--sort-rules" => options.sort_rules = true,
+                "--yes" => options.yes = true,
+                "--strict" => options.strict = true,
+                "--count-models" => options.count_models = true,
+                "--dimacs" => options.dimacs = true,
+                "--conclusions" => options.conclusions = true,
+                "--closure" => options.closure = true,
+                "--as-equivalence" => options.as_equivalence = true,
+                "--allow-unset" => options.allow_unset = true,
+                "--bool-format" => options.bool_format = value(arg, args.next())?,
+                "--format" => options.table_format = value(arg, args.next())?,
+                "--csv-delimiter" => csv.delimiter = value(arg, args.next())?,
+                "--no-header" => csv.header = false,
+                "--implicator-style" => options.implicator_style = value(arg, args.next())?,
+                "--dialect" => options.dialect = value(arg, args.next())?,
+                "--abduce" => match value(arg, args.next())? {
+                    c if is_identifier(c) => options.abduce = Some(c),
+                    c => return Err(anyhow!("Invalid value for option '{}': '{}'", arg, c)),
+                },
+                "--mode" => options.mode = value(arg, args.next())?,
+                "--or-policy" => options.or_policy = value(arg, args.next())?,
+                "--max-depth" => options.max_depth = Some(value(arg, args.next())?),
+                "--max-threads" => {
+                    options.max_threads = Some(value::<NonZeroUsize>(arg, args.next())?.get())
+                }
+                "--facts" => options.facts_file = Some(value(arg, args.next())?),
+                "--checkpoint" => options.checkpoint = Some(value(arg, args.next())?),
+                #[cfg(feature = "profiling")]
+                "--profile" => options.profile = Some(value(arg, args.next())?),
+                // USAGE lists --profile in every build, so it isn't reported as unknown.
+                #[cfg(not(feature = "profiling"))]
+                "--profile" => {
+                    return Err(anyhow!(
+                        "Option '--profile' is unavailable: built without the profiling feature"
+                    ))
+                }
+                "--query" => options.queries.push(value(arg, args.next())?),
+                "--tokens" => options.tokens = Some(value(arg, args.next())?),
+                "--eval" => options.eval = true,
+                "--shuffle-seed" => options.shuffle_seed = Some(value(arg, args.next())?),
+                a if a.starts_with("--") => return Err(anyhow!("Unknown option: '{}'", a)),
+                a => match input_file {
+                    None => input_file = Some(PathBuf::from(a)),
+                    Some(_) => return Err(anyhow!("Multiple input files given")),
+                },
+            }
+        }
+        // The CSV options can be given before or after `--format csv`.
+        if let TableFormat::Csv(options) = &mut options.table_format {
+            *options = csv;
+        }
+        match (input_file, &options.tokens) {
+            (Some(input_file), _) => options.input_file = input_file,
+            (None, Some(_)) => {}
+            (None, None) if options.eval => {}
+            (None, None) => return Err(anyhow!("No input file given")),
+        }
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod tests_options {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn input_file() -> Result<()> {
+        let options = Options::parse(&args(&["input.txt"]))?;
+        assert_eq!(
+            options,
+            Options {
+                input_file: PathBuf::from("input.txt"),
+                ..Default::default()
+            }
+        );
+        Ok(())
+    }
+
+    // A flag and how to read it from the options.
+    type Flag = (&'static str, fn(&Options) -> bool);
+
+    #[test]
+    fn flags() -> Result<()> {
+        let flags: Vec<Flag> = vec![
+            ("--quiet", |o| o.quiet),
+            ("--no-tables", |o| o.no_tables),
+            ("--proof", |o| o.proof),
+            ("--why", |o| o.why),
+            ("--json", |o| o.json),
+            ("--consistency", |o| o.consistency),
+            ("--relevant", |o| o.relevant),
+            ("--stats", |o| o.stats),
+            ("--coverage", |o| o.coverage),
+            ("--verbose", |o| o.verbose),
+            ("--exit-code", |o| o.exit_code),
+            ("--explain-contradiction", |o| o.explain_contradiction),
+            ("--explain-undetermined", |o| o.explain_undetermined),
+            ("--fmt", |o| o.fmt),
+            ("--fmt-write", |o| o.fmt_write),
+            ("--expand-bicond", |o| o.expand_bicond),
+            ("--sort-rules", |o| o.sort_rules),
+            ("--yes", |o| o.yes),
+            ("--strict", |o| o.strict),
+            ("--count-models", |o| o.count_models),
+            ("--dimacs", |o| o.dimacs),
+            ("--conclusions", |o| o.conclusions),
+            ("--closure", |o| o.closure),
+            ("--as-equivalence", |o| o.as_equivalence),
+            ("--allow-unset", |o| o.allow_unset),
+            ("--eval", |o| o.eval),
+        ];
+        #[cfg(not(feature = "wasm"))]
+        let flags = {
+            let watch: Flag = ("--watch", |o| o.watch);
+            [flags, vec![watch]].concat()
+        };
+        for (flag, get) in flags {
+            assert!(!get(&Options::default()), "{}", flag);
+            // Flags can be given before or after the input file.
+            for args in [args(&[flag, "input.txt"]), args(&["input.txt", flag])] {
+                let options = Options::parse(&args)?;
+                assert!(get(&options), "{}", flag);
+                assert_eq!(options.input_file, PathBuf::from("input.txt"));
+            }
+        }
+        // --eval reads from stdin, so it doesn't need an input file.
+        assert!(Options::parse(&args(&["--eval"]))?.eval);
+        Ok(())
+    }
+
+    #[test]
+    fn table_format() -> Result<()> {
+        let options = Options::parse(&args(&["input.txt", "--format", "markdown"]))?;
+        assert_eq!(options.table_format, TableFormat::Markdown);
+        let options = Options::parse(&args(&["input.txt"]))?;
+        assert_eq!(options.table_format, TableFormat::Text);
+        Ok(())
+    }
+
+    #[test]
+    fn csv() -> Result<()> {
+        let options = Options::parse(&args(&[
+            "--csv-delimiter",
+            ";",
+            "input.txt",
+            "--format",
+            "csv",
+            "--no-header",
+        ]))?;
+        assert_eq!(
+            options.table_format,
+            TableFormat::Csv(CsvOptions {
+                delimiter: ';',
+                header: false
+            })
+        );
+        let options = Options::parse(&args(&["input.txt", "--format", "csv"]))?;
+        assert_eq!(
+            options.table_format,
+            TableFormat::Csv(CsvOptions::default())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bool_format() -> Result<()> {
+        let options = Options::parse(&args(&["input.txt", "--bool-format", "yesno"]))?;
+        assert_eq!(options.bool_format, BoolFormat::YesNo);
+        Ok(())
+    }
+
+    #[test]
+    fn dialect() -> Result<()> {
+        let options = Options::parse(&args(&["input.txt"]))?;
+        assert_eq!(options.dialect, OperatorSet::default());
+        let options = Options::parse(&args(&["--dialect", "c-style", "input.txt"]))?;
+        assert_eq!(options.dialect, OperatorSet::c_style());
+        assert!(Options::parse(&args(&["--dialect", "pascal", "input.txt"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn implicator_style() -> Result<()> {
+        let options = Options::parse(&args(&["input.txt"]))?;
+        assert_eq!(options.implicator_style, ImplicatorStyle::Both);
+        let options = Options::parse(&args(&["--implicator-style", "thin", "input.txt"]))?;
+        assert_eq!(options.implicator_style, ImplicatorStyle::Thin);
+        Ok(())
+    }
+
+    #[test]
+    fn abduce() -> Result<()> {
+        let options = Options::parse(&args(&["--abduce", "Z", "input.txt"]))?;
+        assert_eq!(options.abduce, Some('Z'));
+        let result = Options::parse(&args(&["--abduce", "z", "input.txt"]));
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid value for option '--abduce': 'z'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn mode() -> Result<()> {
+        let options = Options::parse(&args(&["--mode", "backward", "input.txt"]))?;
+        assert_eq!(options.mode, Mode::Backward);
+        let options = Options::parse(&args(&["input.txt", "--max-depth", "500"]))?;
+        assert_eq!(options.max_depth, Some(500));
+        Ok(())
+    }
+
+    #[test]
+    fn max_threads() -> Result<()> {
+        let options = Options::parse(&args(&["--max-threads", "1", "input.txt"]))?;
+        assert_eq!(options.max_threads, Some(1));
+        let result = Options::parse(&args(&["--max-threads", "0", "input.txt"]));
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid value for option '--max-threads': '0'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn or_policy() -> Result<()> {
+        let options = Options::parse(&args(&["--or-policy", "first", "input.txt"]))?;
+        assert_eq!(options.or_policy, OrPolicy::First);
+        let result = Options::parse(&args(&["--or-policy", "last", "input.txt"]));
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid value for option '--or-policy': 'last'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn queries() -> Result<()> {
+        let options = Options::parse(&args(&["--query", "A", "input.txt", "--query", "!B"]))?;
+        assert_eq!(options.queries, vec!["A", "!B"]);
+        Ok(())
+    }
+
+    #[test]
+    fn facts_file() -> Result<()> {
+        let options = Options::parse(&args(&["input.txt", "--facts", "facts.txt"]))?;
+        assert_eq!(options.facts_file, Some(PathBuf::from("facts.txt")));
+        Ok(())
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn profile() -> Result<()> {
+        let options = Options::parse(&args(&["input.txt", "--profile", "out.svg"]))?;
+        assert_eq!(options.profile, Some(PathBuf::from("out.svg")));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    #[test]
+    fn error_profile_without_feature() {
+        let result = Options::parse(&args(&["input.txt", "--profile", "out.svg"]));
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Option '--profile' is unavailable: built without the profiling feature"
+        );
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn error_watch_with_wasm() {
+        let result = Options::parse(&args(&["--watch", "input.txt"]));
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Option '--watch' is unavailable: built with the wasm feature"
+        );
+    }
+
+    #[test]
+    fn checkpoint() -> Result<()> {
+        let options = Options::parse(&args(&["input.txt", "--checkpoint", "out.txt"]))?;
+        assert_eq!(options.checkpoint, Some(PathBuf::from("out.txt")));
+        Ok(())
+    }
+
+    #[test]
+    fn tokens() -> Result<()> {
+        let options = Options::parse(&args(&["--tokens", "A+B=>C"]))?;
+        assert_eq!(options.tokens, Some("A+B=>C".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn shuffle_seed() -> Result<()> {
+        let options = Options::parse(&args(&["--shuffle-seed", "42", "input.txt"]))?;
+        assert_eq!(options.shuffle_seed, Some(42));
+        Ok(())
+    }
+
+    #[test]
+    fn error_missing_value() {
+        let result = Options::parse(&args(&["input.txt", "--shuffle-seed"]));
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Missing value for option: '--shuffle-seed'"
+        );
+    }
+
+    #[test]
+    fn error_invalid_value() {
+        let result = Options::parse(&args(&["--shuffle-seed", "foo", "input.txt"]));
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid value for option '--shuffle-seed': 'foo'"
+        );
+    }
+
+    #[test]
+    fn error_no_input_file() {
+        let result = Options::parse(&args(&["--proof"]));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "No input file given");
+    }
+
+    #[test]
+    fn error_multiple_input_files() {
+        let result = Options::parse(&args(&["foo", "bar"]));
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Multiple input files given"
+        );
+    }
+
+    #[test]
+    fn error_unknown_option() {
+        let result = Options::parse(&args(&["--foo", "input.txt"]));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "Unknown option: '--foo'");
+    }
+}
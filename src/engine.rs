@@ -0,0 +1,312 @@
+use crate::*;
+use input::Input;
+use parser::TruthTable;
+use permutation_iter::PermutationIter;
+use rule::Rule;
+use solver::{Query, SolveConfig, SolvedState, Solver};
+use state::State;
+use truth::Truth;
+
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::rc::Rc;
+
+// Engine owns a rule set with everything derived from it: the rules concluding every symbol, the
+// truth tables built so far and the facts with the values they lead to. Facts can be added between
+// queries, the values are derived again on the next query after a change. The state of the solver
+// is kept with the values, so explaining a symbol doesn't solve again either.
+pub struct Engine {
+    rules: Vec<Rule>,
+    conclusions: BTreeMap<char, Vec<usize>>,
+    tables: HashMap<String, Rc<TruthTable>>,
+    initial_facts: State,
+    facts: State,
+    config: SolveConfig,
+    values: Option<(HashMap<char, Truth>, SolvedState)>,
+}
+
+impl Engine {
+    pub fn new(rules: Vec<Rule>, facts: State) -> Self {
+        let mut conclusions: BTreeMap<char, Vec<usize>> = BTreeMap::new();
+        for (index, rule) in rules.iter().enumerate() {
+            let mut symbols = BTreeSet::new();
+            for (_, conclusion) in rule.implications() {
+                symbols.append(&mut conclusion.symbols());
+            }
+            for c in symbols {
+                conclusions.entry(c).or_default().push(index);
+            }
+        }
+        Engine {
+            rules,
+            conclusions,
+            tables: HashMap::new(),
+            initial_facts: facts,
+            facts,
+            config: SolveConfig::default(),
+            values: None,
+        }
+    }
+
+    // Parses the rules of the input, the facts of the input are the initial facts.
+    pub fn from_input(input: &Input) -> Result<Self> {
+        let rules = input
+            .rules()
+            .iter()
+            .map(|line| line.parse())
+            .collect::<Result<Vec<Rule>>>()
+            .context("Failed to parse rule")?;
+        rule::check_labels(&rules)?;
+        Ok(Self::with_facts(rules, input.facts()))
+    }
+
+    // Builds an engine of which the initial facts are given like those of an input, e.g. `A!B`.
+    pub fn with_facts(rules: Vec<Rule>, facts: &str) -> Self {
+        let mut state = State::default();
+        for query in Query::parse_all(facts) {
+            state.set(query.symbol, !query.negated);
+        }
+        Self::new(rules, state)
+    }
+
+    // Solves with the default value, depth limit and or policy of the config, the mode doesn't
+    // apply since an engine answers any symbol.
+    pub fn with_config(mut self, config: SolveConfig) -> Self {
+        self.config = config;
+        self.values = None;
+        self
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    // Returns the rules that conclude the symbol in rule order, see inference::conclusion_groups.
+    pub fn conclusions(&self, symbol: char) -> Vec<&Rule> {
+        self.conclusions
+            .get(&symbol)
+            .into_iter()
+            .flatten()
+            .map(|index| &self.rules[*index])
+            .collect()
+    }
+
+    // Returns the concluded symbols in sorted order, each with the rules concluding it.
+    pub fn conclusion_groups(&self) -> BTreeMap<char, Vec<&Rule>> {
+        self.conclusions
+            .keys()
+            .map(|c| (*c, self.conclusions(*c)))
+            .collect()
+    }
+
+    // Returns the truth table of the rule at the index, it is only built the first time.
+    pub fn table(&mut self, index: usize) -> Result<Rc<TruthTable>> {
+        let formula = &self
+            .rules
+            .get(index)
+            .context(format!("Rule index out of range: {}", index))?
+            .formula;
+        if let Some(table) = self.tables.get(formula) {
+            return Ok(table.clone());
+        }
+        let table = PermutationIter::try_new(formula.as_str())
+            .and_then(TruthTable::try_from)
+            .map(Rc::new)
+            .context(format!("Failed to create truth table from: '{}'", formula))?;
+        self.tables.insert(formula.clone(), table.clone());
+        Ok(table)
+    }
+
+    pub fn facts(&self) -> &State {
+        &self.facts
+    }
+
+    // Adds a fact that is true or false, a fact that is already given is not added again.
+    pub fn add_fact(&mut self, symbol: char, value: bool) -> Result<()> {
+        if !is_identifier(symbol) {
+            return Err(anyhow!("Invalid identifier in facts: '{}'", symbol));
+        }
+        match self.facts.get(symbol) {
+            Some(v) if v != value => Err(anyhow!("Fact '{}' is both true and false", symbol)),
+            Some(_) => Ok(()),
+            None => {
+                self.facts.set(symbol, value);
+                self.values = None;
+                Ok(())
+            }
+        }
+    }
+
+    // Goes back to the initial facts, the truth tables built so far are kept.
+    pub fn reset(&mut self) {
+        self.facts = self.initial_facts;
+        self.values = None;
+    }
+
+    // Derives the values of all symbols from the facts, unless they are derived already.
+    pub fn forward(&mut self) {
+        if self.values.is_some() {
+            return;
+        }
+        let solver = self.solve();
+        let values = self
+            .rules
+            .iter()
+            .flat_map(|r| r.symbols())
+            .chain(self.facts.iter().map(|(c, _)| c))
+            .map(|c| (c, solver.get(c)))
+            .collect();
+        self.values = Some((values, solver.into_state()));
+    }
+
+    // Returns the value of the symbol, deriving the values first when the facts changed.
+    pub fn query(&mut self, symbol: char) -> Truth {
+        self.forward();
+        match &self.values {
+            Some((values, _)) => values.get(&symbol).copied().unwrap_or(self.config.default),
+            None => unreachable!("The values are derived by forward"),
+        }
+    }
+
+    // Explains the value of the symbol: what made it true, or why it is false or undetermined. The
+    // values are derived first when the facts changed, like for query.
+    pub fn explain(&mut self, symbol: char) -> Option<String> {
+        self.forward();
+        let Some((values, state)) = self.values.take() else {
+            unreachable!("The values are derived by forward")
+        };
+        let solver = Solver::resume(&self.rules, state);
+        let explanation = explain(&solver, symbol);
+        self.values = Some((values, solver.into_state()));
+        explanation
+    }
+
+    fn solve(&self) -> Solver<'_> {
+        let facts: String = self
+            .facts
+            .iter()
+            .map(|(c, value)| format!("{}{}", if value { "" } else { "!" }, c))
+            .collect();
+        let mut solver = Solver::new(&self.rules, &facts)
+            .with_default(self.config.default)
+            .with_max_depth(self.config.max_depth)
+            .with_stop_on_contradiction(self.config.stop_on_contradiction)
+            .with_cancel(self.config.cancel.clone())
+            .with_or_policy(self.config.or_policy);
+        solver.solve();
+        solver
+    }
+}
+
+fn explain(solver: &Solver, symbol: char) -> Option<String> {
+    match solver.get(symbol) {
+        Truth::True => Some(format!(
+            "{} is true ({})",
+            symbol,
+            solver.justification_of(symbol)?.clause()
+        )),
+        Truth::False => solver.explain_false(symbol),
+        Truth::Undetermined => solver.explain_undetermined(symbol),
+    }
+}
+
+#[cfg(test)]
+mod tests_engine {
+    use super::*;
+    use cancel::CancelFlag;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    fn engine(lines: &[&str], facts: &str) -> Result<Engine> {
+        let input = Input::new(
+            lines.iter().map(|l| l.to_string()).collect(),
+            facts,
+            "",
+            Truth::False,
+        )?;
+        Engine::from_input(&input)
+    }
+
+    #[test]
+    fn query_add_fact_query() -> Result<()> {
+        let mut engine = engine(&["A + B => C", "C => D"], "A")?;
+        assert_eq!(engine.query('D'), Truth::False);
+        assert_eq!(
+            engine.explain('D').as_deref(),
+            Some("D is false: no rule concluding it fired, closest is rule C=>D with unmet premise symbol C")
+        );
+        engine.add_fact('B', true)?;
+        assert_eq!(engine.query('C'), Truth::True);
+        assert_eq!(engine.query('D'), Truth::True);
+        assert_eq!(engine.explain('D').as_deref(), Some("D is true (via C=>D)"));
+        engine.reset();
+        assert_eq!(engine.query('D'), Truth::False);
+        Ok(())
+    }
+
+    #[test]
+    fn error_add_fact() -> Result<()> {
+        let mut engine = engine(&["A => B"], "!A")?;
+        assert_eq!(
+            engine.add_fact('A', true).unwrap_err().to_string(),
+            "Fact 'A' is both true and false"
+        );
+        assert_eq!(
+            engine.add_fact('a', true).unwrap_err().to_string(),
+            "Invalid identifier in facts: 'a'"
+        );
+        engine.add_fact('A', false)?;
+        assert_eq!(engine.query('B'), Truth::False);
+        Ok(())
+    }
+
+    #[test]
+    fn undetermined() -> Result<()> {
+        let mut engine = engine(&["A => B | C"], "A")?;
+        assert_eq!(engine.query('B'), Truth::Undetermined);
+        assert_eq!(
+            engine.explain('B').as_deref(),
+            Some("B is undetermined: rule A=>B|C allows it to be both true and false")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn explain_cached() -> Result<()> {
+        let cancel = CancelFlag::new();
+        let config = SolveConfig::builder().cancel(cancel.clone()).build();
+        let mut engine = engine(&["A => B"], "A")?.with_config(config);
+        assert_eq!(engine.query('B'), Truth::True);
+        // Solving again would stop before B is derived, the values derived already are explained.
+        cancel.cancel();
+        assert_eq!(engine.explain('B').as_deref(), Some("B is true (via A=>B)"));
+        assert_eq!(engine.query('B'), Truth::True);
+        Ok(())
+    }
+
+    #[test]
+    fn conclusions() -> Result<()> {
+        let engine = engine(&["A => B", "C <=> B", "A => D"], "")?;
+        assert_eq!(engine.conclusions('B').len(), 2);
+        assert_eq!(engine.conclusions('A'), Vec::<&Rule>::new());
+        assert_eq!(
+            engine.conclusion_groups(),
+            inference::conclusion_groups(engine.rules())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn table_cache() -> Result<()> {
+        let mut engine = engine(&["A => B", "A => B"], "")?;
+        let table = engine.table(0)?;
+        assert!(Rc::ptr_eq(&table, &engine.table(1)?));
+        assert_eq!(table.rows(), 4);
+        assert_eq!(
+            engine.table(2).unwrap_err().to_string(),
+            "Rule index out of range: 2"
+        );
+        Ok(())
+    }
+}
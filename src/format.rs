@@ -0,0 +1,167 @@
+use crate::*;
+use input::Input;
+use rule::Rule;
+use truth::Truth;
+
+use anyhow::{Context, Result};
+
+// Formats the input in canonical form: the rules in canonical spacing followed by the facts and
+// the queries, each section separated by an empty line. A `%default` directive other than false
+// comes first. Comments are not preserved.
+pub fn format_input(input: &Input) -> Result<String> {
+    Ok(write_input(input, &parse_rules(input)?, input.facts()))
+}
+
+// Formats the input in canonical form like format_input, with the given facts in place of those of
+// the input. With the facts derived by solving, see Solver::derived_facts, this freezes the state
+// of the inference into an input file that solves to the same values.
+pub fn checkpoint_input(input: &Input, facts: &str) -> Result<String> {
+    Ok(write_input(input, &parse_rules(input)?, facts))
+}
+
+// Formats the input in canonical form like format_input, with every biconditional rule rewritten
+// into its two implications.
+pub fn expand_input(input: &Input) -> Result<String> {
+    Ok(write_input(
+        input,
+        &rule::expand_biconditionals(&parse_rules(input)?),
+        input.facts(),
+    ))
+}
+
+fn parse_rules(input: &Input) -> Result<Vec<Rule>> {
+    let rules = input
+        .rules()
+        .iter()
+        .map(|line| {
+            line.parse()
+                .context(format!("Failed to format rule: '{}'", line))
+        })
+        .collect::<Result<Vec<Rule>>>()?;
+    rule::check_labels(&rules)?;
+    Ok(rules)
+}
+
+fn write_input(input: &Input, rules: &[Rule], facts: &str) -> String {
+    let mut result = String::new();
+    if input.default() != Truth::False {
+        result.push_str(&format!("%default {}\n\n", input.default()));
+    }
+    for rule in rules.iter() {
+        result.push_str(&rule.canonical());
+        result.push('\n');
+    }
+    if !rules.is_empty() {
+        result.push('\n');
+    }
+    result.push_str(&format!("={}\n\n?{}\n", facts, input.queries()));
+    for expression in input.tables() {
+        result.push_str(&format!("?#{}\n", expression));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests_format_input {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn messy() -> Result<()> {
+        let input: Input = indoc! {"
+            # comment
+            A+B   =>C # comment
+              [2](A|B)   <=> !  C
+
+            =  AB
+            ?C
+        "}
+        .parse()?;
+        assert_eq!(
+            format_input(&input)?,
+            indoc! {"
+                A + B => C
+                [2] A | B <=> !C
+
+                =AB
+
+                ?C
+            "}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn checkpoint() -> Result<()> {
+        let input: Input = "A=>B\nB=>C\nD=>E\n=A\n?CE".parse()?;
+        let rules = parse_rules(&input)?;
+        let config = solver::SolveConfig::default();
+        let outcome = solver::run(&rules, input.facts(), input.queries(), &config);
+        let checkpoint = checkpoint_input(&input, &outcome.derived_facts)?;
+        assert_eq!(checkpoint, "A => B\nB => C\nD => E\n\n=ABC\n\n?CE\n");
+
+        // The checkpoint solves to the same values, every true one given as a fact.
+        let input: Input = checkpoint.parse()?;
+        let resumed = solver::run(
+            &parse_rules(&input)?,
+            input.facts(),
+            input.queries(),
+            &config,
+        );
+        assert_eq!(resumed.answers, outcome.answers);
+        assert_eq!(resumed.derived_facts, outcome.derived_facts);
+        assert_eq!(
+            resumed.justifications[&solver::Query::from('C')],
+            solver::Justification::Fact
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn table_queries() -> Result<()> {
+        let input: Input = "=A\n?# A + B # table\n?B".parse()?;
+        assert_eq!(format_input(&input)?, "=A\n\n?B\n?#A+B\n");
+        Ok(())
+    }
+
+    #[test]
+    fn idempotent() -> Result<()> {
+        let formatted = indoc! {"
+            A + B => C
+            [2] A | (B ^ C) <=> !C
+
+            =AB
+
+            ?C
+        "};
+        assert_eq!(format_input(&formatted.parse()?)?, formatted);
+        Ok(())
+    }
+
+    #[test]
+    fn no_rules() -> Result<()> {
+        assert_eq!(format_input(&"=\n?Z".parse()?)?, "=\n\n?Z\n");
+        Ok(())
+    }
+
+    #[test]
+    fn default_directive() -> Result<()> {
+        let input: Input = "% default undetermined\nA => B\n=\n?Z".parse()?;
+        let formatted = format_input(&input)?;
+        assert_eq!(formatted, "%default undetermined\n\nA => B\n\n=\n\n?Z\n");
+        assert_eq!(formatted.parse::<Input>()?, input);
+        Ok(())
+    }
+
+    #[test]
+    fn expand() -> Result<()> {
+        let input: Input = "A => B\n[2] (A + B) <=> C\n=A\n?C".parse()?;
+        assert_eq!(
+            expand_input(&input)?,
+            "A => B\n[2] A + B => C\n[2] C => A + B\n\n=A\n\n?C\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+use crate::*;
+use node::{Classification, Literal, Node};
+use parser::RuleParser;
+use rule::Rule;
+use solver::{Contradiction, Solver};
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+// Returns the implication node of a rule, which holds for an assignment when the rule does.
+fn implication(rule: &Rule) -> Node {
+    Node::Implication(
+        rule.direction,
+        Box::new(rule.premise.clone()),
+        Box::new(rule.conclusion.clone()),
+    )
+}
+
+// Counts the assignments of all symbols in the rules for which every rule holds. The assignments
+// are enumerated exhaustively, so this is only feasible for a small number of symbols.
+// Fails when there are too many symbols to enumerate.
+pub fn count_models(rules: &[Rule]) -> Result<usize> {
+    let symbols: Vec<char> = rules
+        .iter()
+        .flat_map(|r| r.symbols())
+        .collect::<BTreeSet<char>>()
+        .into_iter()
+        .collect();
+    let nodes: Vec<Node> = rules.iter().map(implication).collect();
+    let rows = permutation_iter::permutation_count(symbols.len())?;
+    let mut state = HashMap::new();
+    let mut count = 0;
+    for permutation in 0..rows {
+        for (i, c) in symbols.iter().enumerate() {
+            state.insert(*c, permutation & (1 << i) != 0);
+        }
+        if nodes.iter().all(|n| matches!(n.eval(&state), Ok(true))) {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+// Evaluation holds the classification of a standalone formula and for how many of its rows it is
+// true, see evaluate_formula.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Evaluation {
+    pub classification: Classification,
+    pub true_rows: usize,
+    pub rows: usize,
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}, {} of {} rows true",
+            self.classification, self.true_rows, self.rows
+        )
+    }
+}
+
+// Evaluates a formula over all assignments of its identifiers. A formula without an implicator is
+// evaluated as an expression, see RuleParser::parse_formula.
+pub fn evaluate_formula(formula: &str) -> Result<Evaluation> {
+    let node = RuleParser::new().parse_formula(formula)?;
+    let (true_rows, rows) = node.count_true()?;
+    Ok(Evaluation {
+        classification: node.classify()?,
+        true_rows,
+        rows,
+    })
+}
+
+// Groups the rules by the symbols they conclude, so each symbol maps to every rule that can set
+// it, in rule order. Both sides of a biconditional rule count as conclusions.
+pub fn conclusion_groups(rules: &[Rule]) -> BTreeMap<char, Vec<&Rule>> {
+    let mut groups: BTreeMap<char, Vec<&Rule>> = BTreeMap::new();
+    for rule in rules.iter() {
+        let mut symbols = BTreeSet::new();
+        for (_, conclusion) in rule.implications() {
+            symbols.append(&mut conclusion.symbols());
+        }
+        for c in symbols {
+            groups.entry(c).or_default().push(rule);
+        }
+    }
+    groups
+}
+
+// Returns the consistency problem of the rules and facts in the DIMACS CNF format of SAT solvers:
+// satisfiable when there is an assignment for which every rule and fact holds. The symbols are
+// numbered in sorted order from 1, a comment line lists the number of every symbol. Every rule
+// gives the clauses of Node::to_cnf and every fact a clause of a single literal.
+pub fn to_dimacs(rules: &[Rule], facts: &str) -> Result<String> {
+    let mut clauses = vec![];
+    for rule in rules.iter() {
+        clauses.append(
+            &mut implication(rule)
+                .to_cnf()
+                .context(format!("Failed to convert rule '{}' to CNF", rule))?,
+        );
+    }
+    let mut negated = false;
+    for c in facts.chars() {
+        if c == '!' {
+            negated = true;
+        } else {
+            clauses.push(vec![Literal { symbol: c, negated }]);
+            negated = false;
+        }
+    }
+
+    let symbols: Vec<char> = rules
+        .iter()
+        .flat_map(|r| r.symbols())
+        .chain(facts.chars().filter(|c| is_identifier(c)))
+        .collect::<BTreeSet<char>>()
+        .into_iter()
+        .collect();
+    let number = |literal: &Literal| {
+        let n = symbols
+            .binary_search(&literal.symbol)
+            .expect("Symbol is numbered") as i64
+            + 1;
+        if literal.negated {
+            -n
+        } else {
+            n
+        }
+    };
+    let mut result = String::new();
+    for (i, c) in symbols.iter().enumerate() {
+        result.push_str(&format!("c {} {}\n", i + 1, c));
+    }
+    result.push_str(&format!("p cnf {} {}\n", symbols.len(), clauses.len()));
+    for clause in clauses.iter() {
+        for literal in clause.iter() {
+            result.push_str(&format!("{} ", number(literal)));
+        }
+        result.push_str("0\n");
+    }
+    Ok(result)
+}
+
+// Solves the rules from the facts to a fixpoint and returns the first contradiction found, if any.
+// This doesn't depend on the queries, so it checks the knowledge base as a whole.
+pub fn is_consistent(rules: &[Rule], facts: &str) -> Result<(), Contradiction> {
+    let mut solver = Solver::new(rules, facts);
+    solver.solve();
+    match solver.contradictions.into_iter().next() {
+        Some(contradiction) => Err(contradiction),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests_count_models {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn two_rules() -> Result<()> {
+        // Of the 8 assignments of A, B and C, `A => B` excludes A=1 B=0 and `B <=> C` leaves
+        // B=C: 000, 011, 111.
+        assert_eq!(count_models(&test_utils::rules(&["A=>B", "B<=>C"])?)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn no_rules() -> Result<()> {
+        assert_eq!(count_models(&[])?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn unsatisfiable() -> Result<()> {
+        assert_eq!(count_models(&test_utils::rules(&["1=>A", "1=>!A"])?)?, 0);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_conclusion_groups {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn shared_conclusion() -> Result<()> {
+        let rules = test_utils::rules(&["A+B=>Z", "C=>Z|Y", "Y<=>D"])?;
+        let groups = conclusion_groups(&rules);
+        assert_eq!(groups.keys().collect::<String>(), "DYZ");
+        assert_eq!(groups[&'Z'], vec![&rules[0], &rules[1]]);
+        assert_eq!(groups[&'Y'], vec![&rules[1], &rules[2]]);
+        assert_eq!(groups[&'D'], vec![&rules[2]]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_to_dimacs {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn implication() -> Result<()> {
+        assert_eq!(
+            to_dimacs(&test_utils::rules(&["A=>B"])?, "")?,
+            indoc! {"
+                c 1 A
+                c 2 B
+                p cnf 2 1
+                -1 2 0
+            "}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn header() -> Result<()> {
+        let dimacs = to_dimacs(&test_utils::rules(&["A+B=>C", "C<=>!D"])?, "A!E")?;
+        let header = dimacs.lines().find(|l| l.starts_with("p ")).unwrap();
+        assert_eq!(header, "p cnf 5 5");
+        let clauses = dimacs
+            .lines()
+            .filter(|l| !l.starts_with(['c', 'p']))
+            .collect::<Vec<&str>>();
+        assert_eq!(clauses.len(), 5);
+        assert!(clauses.iter().all(|c| c.ends_with(" 0")));
+        assert_eq!(&clauses[3..], ["1 0", "-5 0"]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_is_consistent {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn consistent() -> Result<()> {
+        assert_eq!(
+            is_consistent(&test_utils::rules(&["A=>B", "B=>!C"])?, "A"),
+            Ok(())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inconsistent() -> Result<()> {
+        let result = is_consistent(&test_utils::rules(&["A=>B", "A=>!B", "B=>C"])?, "A");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Contradiction for 'B': rule 'A=>B' makes it true but rule 'A=>!B' makes it false"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_evaluate_formula {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn classifications() -> Result<()> {
+        assert_eq!(
+            evaluate_formula("A|!A")?.to_string(),
+            "tautology, 2 of 2 rows true"
+        );
+        assert_eq!(
+            evaluate_formula("A+!A")?.to_string(),
+            "contradiction, 0 of 2 rows true"
+        );
+        assert_eq!(
+            evaluate_formula("A=>B")?,
+            Evaluation {
+                classification: Classification::Contingent,
+                true_rows: 3,
+                rows: 4,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_invalid_formula() {
+        let result = evaluate_formula("A+");
+        assert!(result.is_err());
+    }
+}
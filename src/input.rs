@@ -1,14 +1,42 @@
 use crate::*;
+use operator::OperatorSet;
+use parser::ImplicatorStyle;
+use rule::Rule;
+use truth::Truth;
 
 use anyhow::{anyhow, Context, Result};
 use core::fmt;
-use std::{borrow::Borrow, collections::HashSet, path::PathBuf};
+use std::{
+    borrow::{Borrow, Cow},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    io::BufRead,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
+// SymbolRole is a part a symbol plays in the input, a symbol can have several. A premise only or
+// query only symbol is never concluded, so it can only be given as a fact.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SymbolRole {
+    // The symbol is given as a fact, true or false.
+    Fact,
+    // The symbol appears in the conclusion of a rule, both sides of a biconditional rule count.
+    Derivable,
+    // The symbol is queried and appears in no rule.
+    QueryOnly,
+    // The symbol appears in the premises of rules but in no conclusion.
+    PremiseOnly,
+}
+
+// Input holds the sections of an input file. The facts and queries are always valid and hold every
+// fact and query once, so they are only changed through add_fact and add_query.
 #[derive(PartialEq)]
 pub struct Input {
-    pub rules: Vec<String>,
-    pub facts: String,
-    pub queries: String,
+    rules: Vec<String>,
+    facts: String,
+    queries: String,
+    tables: Vec<String>,
+    default: Truth,
 }
 
 impl fmt::Debug for Input {
@@ -19,79 +47,584 @@ impl fmt::Debug for Input {
         }
         writeln!(f, "Facts: {}", self.facts)?;
         writeln!(f, "Queries: {}", self.queries)?;
+        if !self.tables.is_empty() {
+            writeln!(f, "Tables: {}", self.tables.join(", "))?;
+        }
+        if self.default != Truth::False {
+            writeln!(f, "Default: {}", self.default)?;
+        }
         Ok(())
     }
 }
 
-impl TryFrom<PathBuf> for Input {
-    type Error = anyhow::Error;
-
-    fn try_from(file_path: PathBuf) -> Result<Self, Self::Error> {
-        let content: Vec<String> = read_file(&file_path)
-            .context(format!("Failed to read input file: '{:?}'", file_path))?;
-        Self::try_from(content)
+// Parses a `%` directive line, the leading `%` is already removed. Whitespace is ignored.
+fn parse_default(directive: &str) -> Result<Truth> {
+    let directive = sanitize::remove_spaces(directive);
+    match directive.strip_prefix("default") {
+        Some("false") => Ok(Truth::False),
+        Some("undetermined") => Ok(Truth::Undetermined),
+        Some(value) => Err(anyhow!("Invalid default value: '{}'", value)),
+        None => Err(anyhow!("Unknown directive: '%{}'", directive)),
     }
 }
 
-impl<T> TryFrom<Vec<T>> for Input
-where
-    T: Borrow<str>,
-{
-    type Error = anyhow::Error;
+// Reads a file, expanding environment variables on every line. Every `%include <FILE>` line is
+// replaced by the lines of that file, which is resolved relative to the including file.
+fn read_lines(file_path: &Path, allow_unset: bool) -> Result<Vec<String>> {
+    read_included(file_path, allow_unset, &mut vec![])
+}
 
-    fn try_from(lines: Vec<T>) -> Result<Self, Self::Error> {
-        let mut lines = sanitize::sanitize_lines(&lines);
-
-        let mut rules: Vec<String> = vec![];
-        let mut facts: Option<String> = None;
-        let mut queries: Option<String> = None;
-        for line in lines.iter_mut() {
-            match line {
-                l if l.starts_with('=') || l.starts_with('?') => match l.remove(0) {
-                    '=' => match facts {
-                        None => facts = Some(l.to_string()),
-                        Some(_) => return Err(anyhow!("Multiple facts found in input file")),
-                    },
-                    '?' => match queries {
-                        None => queries = Some(l.to_string()),
-                        Some(_) => return Err(anyhow!("Multiple queries found in input file")),
-                    },
-                    _ => unreachable!(),
-                },
-                l if !l.is_empty() => rules.push(l.to_string()),
-                _ => continue,
-            }
-        }
+// Reads a file like read_lines, the stack holds the files that are being included to detect an
+// include cycle.
+fn read_included(
+    file_path: &Path,
+    allow_unset: bool,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Vec<String>> {
+    let content: Vec<String> =
+        read_file(&file_path).context(format!("Failed to read input file: '{:?}'", file_path))?;
+    let lines = content
+        .iter()
+        .map(|line| sanitize::expand_env(line.as_str(), allow_unset))
+        .collect::<Result<Vec<String>>>()
+        .context(format!("Failed to expand input file: '{:?}'", file_path))?;
 
-        let facts = facts.context("No facts in input file")?;
-        if let Some(c) = facts.chars().find(|c| !is_identifier(c)) {
-            return Err(anyhow!("Invalid identifier in facts: '{}'", c));
-        }
-        let queries = queries.context("No queries in input file")?;
-        if let Some(c) = queries.chars().find(|c| !is_identifier(c)) {
-            return Err(anyhow!("Invalid identifier in query: '{}'", c));
+    let canonical = file_path
+        .canonicalize()
+        .context(format!("Failed to read input file: '{:?}'", file_path))?;
+    if stack.contains(&canonical) {
+        return Err(anyhow!("Include cycle: '{:?}'", file_path));
+    }
+    stack.push(canonical);
+    let mut result = vec![];
+    for line in lines {
+        match sanitize::remove_comment(line.as_str())
+            .trim()
+            .strip_prefix("%include")
+        {
+            Some(include) if include.is_empty() || include.starts_with(char::is_whitespace) => {
+                let include = include.trim();
+                if include.is_empty() {
+                    return Err(anyhow!("Missing file in include: '{}'", line));
+                }
+                let include_path = file_path.parent().unwrap_or(Path::new("")).join(include);
+                result.extend(
+                    read_included(&include_path, allow_unset, stack)
+                        .context(format!("Failed to include file: '{:?}'", include_path))?,
+                );
+            }
+            _ => result.push(line),
         }
+    }
+    stack.pop();
+    Ok(result)
+}
 
+impl Input {
+    // Creates the input from its sections, checking the facts and queries and removing repeated
+    // ones. The sets only filter, the facts and queries are kept in source order of their first
+    // occurrence. The rules are not parsed.
+    pub fn new(rules: Vec<String>, facts: &str, queries: &str, default: Truth) -> Result<Self> {
+        check_facts(facts, OperatorSet::default())?;
         let mut fact_set = HashSet::new();
         let mut queries_set = HashSet::new();
         Ok(Input {
             rules,
             facts: facts
-                .chars()
-                .filter(|c| fact_set.insert(c.to_owned()))
+                .split_inclusive(is_identifier)
+                .filter(|f| fact_set.insert(f.to_owned()))
                 .collect(),
-            queries: queries
-                .chars()
-                .filter(|c| queries_set.insert(c.to_owned()))
+            queries: split_queries(queries, OperatorSet::default())?
+                .into_iter()
+                .filter(|q| queries_set.insert(q.to_owned()))
                 .collect(),
+            tables: vec![],
+            default,
         })
     }
+
+    pub fn rules(&self) -> &[String] {
+        &self.rules
+    }
+
+    pub fn facts(&self) -> &str {
+        &self.facts
+    }
+
+    pub fn queries(&self) -> &str {
+        &self.queries
+    }
+
+    pub fn default(&self) -> Truth {
+        self.default
+    }
+
+    // Returns the expressions of the `?#` lines, of which the truth table is printed instead of
+    // resolving them against the facts.
+    pub fn tables(&self) -> &[String] {
+        &self.tables
+    }
+
+    // Iterates over the facts in source order, e.g. `A` and `!B` for `A!B`.
+    pub fn iter_facts(&self) -> impl Iterator<Item = &str> {
+        self.facts.split_inclusive(is_identifier)
+    }
+
+    // Iterates over the queries in source order, e.g. `Z` and `!Y|A` for `Z!Y|A`.
+    pub fn iter_queries(&self) -> impl Iterator<Item = &str> {
+        split_queries(&self.queries, OperatorSet::default())
+            .expect("The queries are checked on construction")
+            .into_iter()
+    }
+
+    // Returns the roles of every symbol of the rules, facts and queries. The assumptions of a
+    // query are not counted.
+    pub fn symbol_roles(&self) -> Result<BTreeMap<char, BTreeSet<SymbolRole>>> {
+        let rules = self
+            .rules
+            .iter()
+            .map(|line| line.parse::<Rule>())
+            .collect::<Result<Vec<Rule>>>()
+            .context("Failed to parse rule")?;
+        let mut premises = BTreeSet::new();
+        let mut conclusions = BTreeSet::new();
+        for (premise, conclusion) in rules.iter().flat_map(|r| r.implications()) {
+            premises.append(&mut premise.symbols());
+            conclusions.append(&mut conclusion.symbols());
+        }
+
+        let mut roles: BTreeMap<char, BTreeSet<SymbolRole>> = BTreeMap::new();
+        for c in self.facts.chars().filter(|c| is_identifier(c)) {
+            roles.entry(c).or_default().insert(SymbolRole::Fact);
+        }
+        for c in conclusions.iter() {
+            roles.entry(*c).or_default().insert(SymbolRole::Derivable);
+        }
+        for c in premises.difference(&conclusions) {
+            roles.entry(*c).or_default().insert(SymbolRole::PremiseOnly);
+        }
+        for query in self.iter_queries() {
+            let c = query
+                .chars()
+                .find(|c| is_identifier(c))
+                .expect("A query holds a symbol");
+            if !premises.contains(&c) && !conclusions.contains(&c) {
+                roles.entry(c).or_default().insert(SymbolRole::QueryOnly);
+            }
+        }
+        Ok(roles)
+    }
+
+    // Summarizes the size of the input, e.g. `Loaded 2 rules, 1 fact, 3 queries over 4 symbols`.
+    // The symbols are those of symbol_roles.
+    pub fn summary(&self) -> Result<String> {
+        let count = |n: usize, singular: &str, plural: &str| {
+            format!("{} {}", n, if n == 1 { singular } else { plural })
+        };
+        Ok(format!(
+            "Loaded {}, {}, {} over {}",
+            count(self.rules.len(), "rule", "rules"),
+            count(self.iter_facts().count(), "fact", "facts"),
+            count(self.iter_queries().count(), "query", "queries"),
+            count(self.symbol_roles()?.len(), "symbol", "symbols")
+        ))
+    }
+
+    // Adds a fact that is true or false, a fact that is already given is not added again.
+    pub fn add_fact(&mut self, symbol: char, value: bool) -> Result<()> {
+        if !is_identifier(symbol) {
+            return Err(invalid_identifier("facts", symbol, OperatorSet::default()));
+        }
+        let fact = if value {
+            symbol.to_string()
+        } else {
+            format!("!{}", symbol)
+        };
+        check_facts(&format!("{}{}", self.facts, fact), OperatorSet::default())?;
+        if !self.facts.contains(symbol) {
+            self.facts.push_str(&fact);
+        }
+        Ok(())
+    }
+
+    // Adds a single query, e.g. `!Z` or `Z|A,B`, a query that is already given is not added again.
+    pub fn add_query(&mut self, query: &str) -> Result<()> {
+        match split_queries(query, OperatorSet::default())?[..] {
+            [query] => {
+                if !split_queries(&self.queries, OperatorSet::default())?.contains(&query) {
+                    self.queries.push_str(query);
+                }
+                Ok(())
+            }
+            _ => Err(anyhow!("Expected a single query: '{}'", query)),
+        }
+    }
+
+    // Replaces the queries by the given ones, each a single query like for add_query.
+    pub fn set_queries(&mut self, queries: &[impl Borrow<str>]) -> Result<()> {
+        self.queries.clear();
+        for query in queries.iter() {
+            self.add_query(query.borrow())?;
+        }
+        Ok(())
+    }
+
+    // Rewrites the rules written with the operators of the set into the default operators, so the
+    // rest of the input handling can read them. Every rule is checked against the set first.
+    pub fn translate_operators(&mut self, operators: OperatorSet) -> Result<()> {
+        for line in self.rules.iter_mut() {
+            Rule::parse_with_operators(line, ImplicatorStyle::Both, operators)?;
+            *line = operators.to_default(line);
+        }
+        Ok(())
+    }
+
+    // Sorts the rules into canonical order, see rule::sort_rules. The lines of the rules are kept
+    // as they are.
+    pub fn sort_rules(&mut self) -> Result<()> {
+        let mut rules = self
+            .rules
+            .iter()
+            .map(|line| Ok((line.parse::<Rule>()?, line.clone())))
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to sort rules")?;
+        rules.sort_by_cached_key(|(rule, _)| rule::sort_key(rule));
+        self.rules = rules.into_iter().map(|(_, line)| line).collect();
+        Ok(())
+    }
+
+    // Reads the input file, expanding environment variables on every line before parsing. The
+    // rules are written with the operators of the set, which only decide the hint of an invalid
+    // fact or query, see translate_operators for the rules themselves.
+    pub fn load(file_path: &Path, allow_unset: bool, operators: OperatorSet) -> Result<Self> {
+        Self::from_lines(read_lines(file_path, allow_unset)?, operators)
+    }
+
+    fn from_lines(lines: Vec<impl Borrow<str>>, operators: OperatorSet) -> Result<Self> {
+        let mut sections = Sections {
+            operators,
+            ..Default::default()
+        };
+        for line in sanitize::sanitize_lines(&lines)? {
+            sections.push(&line)?;
+        }
+        sections.finish()
+    }
+
+    // Parses the input line by line from a reader, stopping at the first invalid line without
+    // reading the rest. Unlike the other constructors this also checks the syntax of every rule.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let mut sections = Sections::default();
+        let mut continued: Option<String> = None;
+        for (number, reader_line) in reader.lines().enumerate() {
+            let mut line = continued.take().unwrap_or_default();
+            line.push_str(&sanitize::sanitize_line(
+                reader_line.context("Failed to read input")?,
+            ));
+            if let Some(rest) = sanitize::strip_continuation(&line)
+                .context(format!("Invalid input on line {}", number + 1))?
+            {
+                continued = Some(rest.to_string());
+                continue;
+            }
+            let rules = sections.rules.len();
+            sections
+                .push(&line)
+                .context(format!("Invalid input on line {}", number + 1))?;
+            if sections.rules.len() > rules {
+                line.parse::<Rule>()
+                    .context(format!("Invalid rule on line {}", number + 1))?;
+            }
+        }
+        if let Some(line) = continued {
+            return Err(anyhow!("Line continuation at end of input: '{}\\'", line));
+        }
+        sections.finish()
+    }
+
+    // Reads the input file and merges in the facts of a separate facts file, which only holds
+    // `=` lines. The input file doesn't need a facts line of its own in that case. Returns the
+    // facts that were given in both files. The operators are those of load.
+    pub fn load_with_facts(
+        file_path: &Path,
+        facts_path: &Path,
+        allow_unset: bool,
+        operators: OperatorSet,
+    ) -> Result<(Self, Vec<char>)> {
+        let mut lines = read_lines(file_path, allow_unset)?;
+        if !sanitize::sanitize_lines(&lines)?
+            .iter()
+            .any(|l| l.starts_with('=') && !l.starts_with("=>"))
+        {
+            lines.push("=".to_string());
+        }
+        let mut input = Self::from_lines(lines, operators)?;
+
+        let mut facts = String::new();
+        for line in sanitize::sanitize_lines(&read_lines(facts_path, allow_unset)?)? {
+            match line.strip_prefix('=') {
+                Some(f) => facts.push_str(&sanitize::remove_spaces(f)),
+                None if line.is_empty() => {}
+                None => return Err(anyhow!("Invalid line in facts file: '{}'", line)),
+            }
+        }
+        check_facts(&facts, operators)?;
+        let mut duplicates = vec![];
+        for fact in facts.split_inclusive(is_identifier) {
+            let c = fact
+                .chars()
+                .last()
+                .expect("Facts are split after an identifier");
+            if !input.facts.contains(c) {
+                input.facts.push_str(fact);
+            } else if !duplicates.contains(&c) {
+                duplicates.push(c);
+            }
+        }
+        Ok((input, duplicates))
+    }
+}
+
+impl TryFrom<PathBuf> for Input {
+    type Error = anyhow::Error;
+
+    fn try_from(file_path: PathBuf) -> Result<Self, Self::Error> {
+        Self::load(&file_path, false, OperatorSet::default())
+    }
+}
+
+impl FromStr for Input {
+    type Err = anyhow::Error;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        Self::try_from(content.lines().collect::<Vec<&str>>())
+    }
+}
+
+// Strips the braces and commas of the set syntax, e.g. `{A,B}` for `AB`. Content without braces is
+// returned as is.
+fn strip_set<'a>(section: &str, content: &'a str) -> Result<Cow<'a, str>> {
+    match content.strip_prefix('{').map(|c| c.strip_suffix('}')) {
+        None if !content.contains(['{', '}']) => Ok(content.into()),
+        Some(Some(inner)) if !inner.contains(['{', '}']) => Ok(inner.replace(',', "").into()),
+        _ => Err(anyhow!("Mismatched braces in {}: '{}'", section, content)),
+    }
+}
+
+// Chars that only have a meaning inside a rule, along with the operators of the dialect. Finding one
+// in the facts or queries is likely an operator that ended up on the wrong line.
+const RULE_ONLY_CHARS: &str = "()=<>-";
+
+// Returns the error of an invalid identifier in the facts or query section, with a hint when the
+// char is an operator of the set. Facts and queries are negated with `!` in every dialect, so it
+// only gets the hint when it is misplaced in a dialect that doesn't negate with it.
+fn invalid_identifier(section: &str, c: char, operators: OperatorSet) -> anyhow::Error {
+    if RULE_ONLY_CHARS.contains(c) || c != '!' && operators.operator(c).is_some() {
+        anyhow!(
+            "Invalid identifier in {}: '{}' (did you mean an operator inside a rule?)",
+            section,
+            c
+        )
+    } else {
+        anyhow!("Invalid identifier in {}: '{}'", section, c)
+    }
+}
+
+// Splits a queries line, e.g. `A!B~CZ|A,!B`, into its queries, checking the syntax. A query may be
+// followed by `|` and a comma separated list of assumptions, which hold for that query only.
+// The operators only decide the hint of an invalid identifier.
+pub(crate) fn split_queries(queries: &str, operators: OperatorSet) -> Result<Vec<&str>> {
+    // Where the next character is read: at the start of a query, after its symbol, where an
+    // assumption is expected or after an assumption.
+    #[derive(PartialEq)]
+    enum Position {
+        Query,
+        Symbol,
+        Assumption,
+        Assumed,
+    }
+    let mut result = vec![];
+    let mut position = Position::Query;
+    let mut start = 0;
+    let mut negated = false;
+    let mut open = false;
+    let mut assumed = HashMap::new();
+    let mut chars = queries.char_indices().peekable();
+    while let Some(&(i, c)) = chars.peek() {
+        match (&position, c) {
+            (Position::Symbol, '|') => position = Position::Assumption,
+            (Position::Assumed, ',') => position = Position::Assumption,
+            (Position::Symbol | Position::Assumed, _) => {
+                // The query ended, the character starts the next one.
+                result.push(&queries[start..i]);
+                (position, start) = (Position::Query, i);
+                assumed.clear();
+                continue;
+            }
+            (Position::Query, '~') if !open && !negated => open = true,
+            (Position::Query | Position::Assumption, '!') if !negated => negated = true,
+            (Position::Query, c) if is_identifier(c) => {
+                (negated, open, position) = (false, false, Position::Symbol)
+            }
+            (Position::Query, c) => return Err(invalid_identifier("query", c, operators)),
+            (Position::Assumption, c) if is_identifier(c) => {
+                if assumed.insert(c, !negated) == Some(negated) {
+                    return Err(anyhow!("Assumption '{}' is both true and false", c));
+                }
+                (negated, position) = (false, Position::Assumed)
+            }
+            (Position::Assumption, c) => {
+                return Err(anyhow!("Invalid assumption in query: '{}'", c))
+            }
+        }
+        chars.next();
+    }
+    match position {
+        Position::Query if negated => Err(anyhow!("Missing identifier after negation in query")),
+        Position::Query if open => Err(anyhow!("Missing identifier after '~' in query")),
+        Position::Assumption => Err(anyhow!("Missing assumption in query")),
+        Position::Query => Ok(result),
+        Position::Symbol | Position::Assumed => {
+            result.push(&queries[start..]);
+            Ok(result)
+        }
+    }
+}
+
+// Checks a facts line, e.g. `A!B`: every fact is an identifier that may be negated to
+// make it false, and no symbol is both true and false. The operators only decide the hint of an
+// invalid identifier.
+fn check_facts(facts: &str, operators: OperatorSet) -> Result<()> {
+    let mut negated = false;
+    let mut values = HashMap::new();
+    for c in facts.chars() {
+        match c {
+            '!' if !negated => negated = true,
+            c if is_identifier(c) => {
+                if values.insert(c, !negated) == Some(negated) {
+                    return Err(anyhow!("Fact '{}' is both true and false", c));
+                }
+                negated = false;
+            }
+            c => return Err(invalid_identifier("facts", c, operators)),
+        }
+    }
+    if negated {
+        return Err(anyhow!("Missing identifier after negation in facts"));
+    }
+    Ok(())
+}
+
+// Checks the expression of a `?#` line, e.g. `A+B`, which may not hold an implication.
+fn check_table_expression(expression: &str) -> Result<()> {
+    if expression.is_empty() {
+        return Err(anyhow!("Missing expression in table query"));
+    }
+    let mut parser = parser::RuleParser::new();
+    let implication = parser.tokenize(expression).map(|tokens| {
+        tokens
+            .iter()
+            .any(|t| matches!(t, parser::Token::Implicator(_)))
+    });
+    match implication {
+        Ok(false) => parser
+            .parse(&format!("1 => {}", expression))
+            .map(|_| ())
+            .context(format!(
+                "Invalid expression in table query: '{}'",
+                expression
+            )),
+        _ => Err(anyhow!(
+            "Invalid expression in table query: '{}'",
+            expression
+        )),
+    }
+}
+
+// Sections collects the sanitized lines of an input file one at a time, so errors in the facts and
+// queries are found at the line that holds them.
+#[derive(Default)]
+struct Sections {
+    rules: Vec<String>,
+    facts: Option<String>,
+    queries: Option<String>,
+    tables: Vec<String>,
+    default: Option<Truth>,
+    operators: OperatorSet,
+}
+
+impl Sections {
+    fn push(&mut self, line: &str) -> Result<()> {
+        match line {
+            // A line starting with an implicator is a rule without premise, not the facts.
+            l if l.starts_with("=>") => self.rules.push(l.to_string()),
+            l if l.starts_with('=') => match self.facts {
+                None => {
+                    let facts = sanitize::remove_spaces(&l[1..]);
+                    let facts = strip_set("facts", &facts)?;
+                    check_facts(&facts, self.operators)?;
+                    self.facts = Some(facts.to_string());
+                }
+                Some(_) => return Err(anyhow!("Multiple facts found in input file")),
+            },
+            l if l.starts_with("?#") => {
+                let expression = sanitize::remove_spaces(&l[2..]);
+                check_table_expression(&expression)?;
+                self.tables.push(expression);
+            }
+            l if l.starts_with('?') => match self.queries {
+                None => {
+                    let queries = sanitize::remove_spaces(&l[1..]);
+                    let queries = strip_set("queries", &queries)?;
+                    split_queries(&queries, self.operators)?;
+                    self.queries = Some(queries.to_string());
+                }
+                Some(_) => return Err(anyhow!("Multiple queries found in input file")),
+            },
+            l if l.starts_with('%') => match self.default {
+                None => self.default = Some(parse_default(&l[1..])?),
+                Some(_) => return Err(anyhow!("Multiple default directives found in input file")),
+            },
+            l if !l.is_empty() => self.rules.push(l.to_string()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Input> {
+        let facts = self.facts.context("No facts in input file")?;
+        // Table queries take the place of the queries line.
+        let queries = match self.queries {
+            None if !self.tables.is_empty() => String::new(),
+            queries => queries.context("No queries in input file")?,
+        };
+        let mut input = Input::new(
+            self.rules,
+            &facts,
+            &queries,
+            self.default.unwrap_or(Truth::False),
+        )?;
+        input.tables = self.tables;
+        Ok(input)
+    }
+}
+
+impl<T> TryFrom<Vec<T>> for Input
+where
+    T: Borrow<str>,
+{
+    type Error = anyhow::Error;
+
+    fn try_from(lines: Vec<T>) -> Result<Self, Self::Error> {
+        Self::from_lines(lines, OperatorSet::default())
+    }
 }
 
 #[cfg(test)]
 mod tests_input {
     use super::*;
     use crate::test_utils;
+    use std::io::Cursor;
 
     use anyhow::Result;
     use pretty_assertions::assert_eq;
@@ -102,15 +635,224 @@ mod tests_input {
         let result = Input::try_from(input_file)?;
         assert_eq!(
             result,
-            Input {
-                rules: vec!["A=>Z".to_string()],
-                facts: "A".to_string(),
-                queries: "Z".to_string(),
-            }
+            Input::new(vec!["A => Z".to_string()], "A", "Z", Truth::False)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn load_with_facts() -> Result<()> {
+        let (input, duplicates) = Input::load_with_facts(
+            &test_utils::input_file_path("input/valid.txt"),
+            &test_utils::input_file_path("input/facts.txt"),
+            false,
+            OperatorSet::default(),
+        )?;
+        assert_eq!(
+            input,
+            Input::new(vec!["A => Z".to_string()], "ABC", "Z", Truth::False)?
+        );
+        assert_eq!(duplicates, vec!['A']);
+        Ok(())
+    }
+
+    #[test]
+    fn load_with_facts_only() -> Result<()> {
+        let (input, duplicates) = Input::load_with_facts(
+            &test_utils::input_file_path("input/no_facts.txt"),
+            &test_utils::input_file_path("input/facts.txt"),
+            false,
+            OperatorSet::default(),
+        )?;
+        assert_eq!(input.facts, "BCA");
+        assert_eq!(duplicates, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn include() -> Result<()> {
+        let input = Input::load(
+            &test_utils::input_file_path("input/include.txt"),
+            false,
+            OperatorSet::default(),
+        )?;
+        assert_eq!(
+            input,
+            Input::new(
+                vec!["A => Z".to_string(), "Z => Y".to_string()],
+                "A",
+                "Y",
+                Truth::False
+            )?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_include_missing() {
+        let input_file = test_utils::input_file_path("input/include_missing.txt");
+        let result = Input::load(&input_file, false, OperatorSet::default());
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                "Failed to include file: '{:?}'",
+                input_file.parent().unwrap().join("non_exist.txt")
+            )
+        );
+    }
+
+    #[test]
+    fn error_include_cycle() {
+        let input_file = test_utils::input_file_path("input/include_self.txt");
+        let result = Input::load(&input_file, false, OperatorSet::default());
+        assert!(result.is_err());
+        assert_eq!(
+            format!("{:#}", result.unwrap_err()),
+            format!(
+                "Failed to include file: '{:?}': Include cycle: '{:?}'",
+                input_file, input_file
+            )
+        );
+    }
+
+    #[test]
+    fn set_queries() -> Result<()> {
+        let mut input = Input::new(vec!["A=>Z".to_string()], "A", "Z", Truth::False)?;
+        input.set_queries(&["A", "!B", "A"])?;
+        assert_eq!(input.queries(), "A!B");
+        let result = input.set_queries(&["AB"]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Expected a single query: 'AB'"
+        );
+        let result = input.set_queries(&["a"]);
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn symbol_roles() -> Result<()> {
+        let input = Input::load(
+            &test_utils::input_file_path("input/roles.txt"),
+            false,
+            OperatorSet::default(),
+        )?;
+        let roles = |roles: &[SymbolRole]| roles.iter().copied().collect::<BTreeSet<_>>();
+        assert_eq!(
+            input.symbol_roles()?,
+            BTreeMap::from([
+                ('A', roles(&[SymbolRole::Fact, SymbolRole::PremiseOnly])),
+                ('B', roles(&[SymbolRole::PremiseOnly])),
+                ('C', roles(&[SymbolRole::Derivable])),
+                ('D', roles(&[SymbolRole::Fact, SymbolRole::Derivable])),
+                ('E', roles(&[SymbolRole::Derivable])),
+                ('F', roles(&[SymbolRole::Fact, SymbolRole::QueryOnly])),
+                ('G', roles(&[SymbolRole::QueryOnly])),
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn translate_operators() -> Result<()> {
+        let mut input = Input::try_from(vec!["A&~B=>C", "r1:C|D=>E", "=A", "?E"])?;
+        input.translate_operators(OperatorSet::c_style())?;
+        assert_eq!(input.rules(), ["A+!B=>C", "r1:C|D=>E"]);
+        let mut input = Input::try_from(vec!["A+B=>C", "=A", "?C"])?;
+        let result = input.translate_operators(OperatorSet::c_style());
+        assert!(result.is_err());
+        assert_eq!(
+            format!("{:#}", result.unwrap_err()),
+            "Failed to parse rule: 'A+B=>C': Failed to tokenize input: 'A+B=>C': Unexpected character: +"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn summary() -> Result<()> {
+        let input = Input::load(
+            &test_utils::input_file_path("input/roles.txt"),
+            false,
+            OperatorSet::default(),
+        )?;
+        assert_eq!(
+            input.summary()?,
+            "Loaded 3 rules, 3 facts, 3 queries over 7 symbols"
+        );
+        let input = Input::try_from(vec!["A=>B", "=A", "?B"])?;
+        assert_eq!(
+            input.summary()?,
+            "Loaded 1 rule, 1 fact, 1 query over 2 symbols"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn from_reader() -> Result<()> {
+        let content = indoc! {"
+            A => Z # comment
+            B <=> Z
+
+            =AB
+            ?Z
+        "};
+        let result = Input::from_reader(Cursor::new(content))?;
+        assert_eq!(result, content.parse()?);
+        Ok(())
+    }
+
+    #[test]
+    fn continuation() -> Result<()> {
+        let content = indoc! {"
+            A + B \\
+              | C => Z
+            =A
+            ?Z
+        "};
+        let expected = Input::try_from(vec!["A + B | C => Z", "=A", "?Z"])?;
+        assert_eq!(content.parse::<Input>()?, expected);
+        assert_eq!(Input::from_reader(Cursor::new(content))?, expected);
+
+        let result = Input::from_reader(Cursor::new("=A\n?Z\nA => \\\n"));
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Line continuation at end of input: 'A => \\'"
         );
         Ok(())
     }
 
+    #[test]
+    fn error_from_reader() {
+        let content = indoc! {"
+            A => Z
+            =A
+            A =>
+            =B
+            ?Z
+        "};
+        let result = Input::from_reader(Cursor::new(content));
+        assert!(result.is_err());
+        assert_eq!(
+            format!("{:#}", result.unwrap_err()),
+            "Invalid rule on line 3: rule 'A =>' is missing a conclusion"
+        );
+        let result = Input::from_reader(Cursor::new("A = > Z\n=A\n?Z\n"));
+        assert!(result.is_err());
+        assert_eq!(
+            format!("{:#}", result.unwrap_err()),
+            "Invalid rule on line 1: Failed to parse rule: 'A = > Z': Failed to tokenize input: \
+             'A = > Z': Unable to finish lexing implicator"
+        );
+        let result = Input::from_reader(Cursor::new("A => Z\n=A\n=B\n?Z & Y\n"));
+        assert!(result.is_err());
+        assert_eq!(
+            format!("{:#}", result.unwrap_err()),
+            "Invalid input on line 3: Multiple facts found in input file"
+        );
+    }
+
     #[test]
     fn error_from_file_non_exist() {
         let input_file = test_utils::input_file_path("input/non_exist.txt");
@@ -118,15 +860,40 @@ mod tests_input {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn from_str() -> Result<()> {
+        let result: Input = indoc! {"
+            A => Z # comment
+            B <=> Z
+
+            =AB
+            ?Z
+        "}
+        .parse()?;
+        assert_eq!(
+            result,
+            Input::new(
+                vec!["A => Z".to_string(), "B <=> Z".to_string()],
+                "AB",
+                "Z",
+                Truth::False
+            )?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_from_str() {
+        let result = "A => Z\n?Z".parse::<Input>();
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "No facts in input file");
+    }
+
     #[test]
     fn spacing() -> Result<()> {
         assert_eq!(
             Input::try_from(vec!["A=>Z", "=A", "?Z"])?,
-            Input {
-                rules: vec!["A=>Z".to_string()],
-                facts: "A".to_string(),
-                queries: "Z".to_string(),
-            }
+            Input::new(vec!["A=>Z".to_string()], "A", "Z", Truth::False)?
         );
         Ok(())
     }
@@ -135,11 +902,7 @@ mod tests_input {
     fn order() -> Result<()> {
         assert_eq!(
             Input::try_from(vec!["?Z", "=A", "A=>Z"])?,
-            Input {
-                rules: vec!["A=>Z".to_string()],
-                facts: "A".to_string(),
-                queries: "Z".to_string(),
-            }
+            Input::new(vec!["A=>Z".to_string()], "A", "Z", Truth::False)?
         );
         Ok(())
     }
@@ -148,11 +911,12 @@ mod tests_input {
     fn rule_order() -> Result<()> {
         assert_eq!(
             Input::try_from(vec!["A=>Z", "=A", "Z=>A", "?Z"])?,
-            Input {
-                rules: vec!["A=>Z".to_string(), "Z=>A".to_string()],
-                facts: "A".to_string(),
-                queries: "Z".to_string(),
-            }
+            Input::new(
+                vec!["A=>Z".to_string(), "Z=>A".to_string()],
+                "A",
+                "Z",
+                Truth::False
+            )?
         );
         Ok(())
     }
@@ -161,11 +925,7 @@ mod tests_input {
     fn no_rules() -> Result<()> {
         assert_eq!(
             Input::try_from(vec!["=A", "?Z"])?,
-            Input {
-                rules: vec![],
-                facts: "A".to_string(),
-                queries: "Z".to_string(),
-            }
+            Input::new(vec![], "A", "Z", Truth::False)?
         );
         Ok(())
     }
@@ -174,11 +934,7 @@ mod tests_input {
     fn valid() -> Result<()> {
         assert_eq!(
             Input::try_from(vec!["A=>Z", "=A", "?Z"])?,
-            Input {
-                rules: vec!["A=>Z".to_string()],
-                facts: "A".to_string(),
-                queries: "Z".to_string(),
-            }
+            Input::new(vec!["A=>Z".to_string()], "A", "Z", Truth::False)?
         );
         Ok(())
     }
@@ -187,11 +943,7 @@ mod tests_input {
     fn empty_facts() -> Result<()> {
         assert_eq!(
             Input::try_from(vec!["=", "?Z"])?,
-            Input {
-                rules: vec![],
-                facts: "".to_string(),
-                queries: "Z".to_string(),
-            }
+            Input::new(vec![], "", "Z", Truth::False)?
         );
         Ok(())
     }
@@ -200,11 +952,7 @@ mod tests_input {
     fn empty_queries() -> Result<()> {
         assert_eq!(
             Input::try_from(vec!["=A", "?"])?,
-            Input {
-                rules: vec![],
-                facts: "A".to_string(),
-                queries: "".to_string(),
-            }
+            Input::new(vec![], "A", "", Truth::False)?
         );
         Ok(())
     }
@@ -213,28 +961,332 @@ mod tests_input {
     fn duplicate_facts() -> Result<()> {
         assert_eq!(
             Input::try_from(vec!["=AA", "?"])?,
-            Input {
-                rules: vec![],
-                facts: "A".to_string(),
-                queries: "".to_string(),
-            }
+            Input::new(vec![], "A", "", Truth::False)?
         );
         Ok(())
     }
 
+    #[test]
+    fn source_order() -> Result<()> {
+        let input = Input::try_from(vec!["=CA!BCDA", "?ZA!ZAY|B,C"])?;
+        assert_eq!(
+            input.iter_facts().collect::<Vec<_>>(),
+            vec!["C", "A", "!B", "D"]
+        );
+        assert_eq!(
+            input.iter_queries().collect::<Vec<_>>(),
+            vec!["Z", "A", "!Z", "Y|B,C"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn false_facts() -> Result<()> {
+        let input = Input::try_from(vec!["=A!B!BA", "?B"])?;
+        assert_eq!(input.facts, "A!B");
+        Ok(())
+    }
+
+    #[test]
+    fn error_false_facts() {
+        let result = Input::try_from(vec!["=A!", "?B"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Missing identifier after negation in facts"
+        );
+        let result = Input::try_from(vec!["=B!B", "?B"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Fact 'B' is both true and false"
+        );
+    }
+
+    #[test]
+    fn error_operator_identifiers() -> Result<()> {
+        for (lines, message) in [
+            (
+                ["=+", "?A"],
+                "Invalid identifier in facts: '+' (did you mean an operator inside a rule?)",
+            ),
+            (
+                ["=A", "?|"],
+                "Invalid identifier in query: '|' (did you mean an operator inside a rule?)",
+            ),
+            (["=a", "?A"], "Invalid identifier in facts: 'a'"),
+        ] {
+            let result = Input::try_from(lines.to_vec());
+            assert!(result.is_err());
+            assert_eq!(result.unwrap_err().to_string(), message);
+        }
+        assert_eq!(Input::try_from(vec!["=A!B", "?A"])?.facts, "A!B");
+        // The hint follows the operators of the dialect.
+        for (operators, message) in [
+            (OperatorSet::default(), "Invalid identifier in facts: '&'"),
+            (
+                OperatorSet::c_style(),
+                "Invalid identifier in facts: '&' (did you mean an operator inside a rule?)",
+            ),
+        ] {
+            let result = Input::from_lines(vec!["=A&B", "?A"], operators);
+            assert_eq!(result.unwrap_err().to_string(), message);
+        }
+        let result = Input::from_lines(vec!["=A+B", "?A"], OperatorSet::c_style());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid identifier in facts: '+'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn table_queries() -> Result<()> {
+        let input = Input::try_from(vec!["A=>B", "=A", "?#A+B", "?# !(A | C)"])?;
+        assert_eq!(input.tables(), ["A+B", "!(A|C)"]);
+        assert_eq!(input.queries(), "");
+        let input = Input::try_from(vec!["=A", "?B", "?#A^B"])?;
+        assert_eq!(input.tables(), ["A^B"]);
+        assert_eq!(input.queries(), "B");
+        Ok(())
+    }
+
+    #[test]
+    fn error_table_queries() {
+        for (lines, message) in [
+            (["=A", "?#"], "Missing expression in table query"),
+            (
+                ["=A", "?#A=>B"],
+                "Invalid expression in table query: 'A=>B'",
+            ),
+            (["=A", "?#A+"], "Invalid expression in table query: 'A+'"),
+        ] {
+            let result = Input::try_from(lines.to_vec());
+            assert!(result.is_err());
+            assert_eq!(result.unwrap_err().to_string(), message);
+        }
+    }
+
     #[test]
     fn duplicate_queries() -> Result<()> {
         assert_eq!(
             Input::try_from(vec!["=", "?ZZ"])?,
-            Input {
-                rules: vec![],
-                facts: "".to_string(),
-                queries: "Z".to_string(),
-            }
+            Input::new(vec![], "", "Z", Truth::False)?
         );
         Ok(())
     }
 
+    #[test]
+    fn negated_queries() -> Result<()> {
+        assert_eq!(
+            Input::try_from(vec!["=", "?!ZZ!Z!A"])?,
+            Input::new(vec![], "", "!ZZ!A", Truth::False)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_negated_queries() {
+        let result = Input::try_from(vec!["=", "?Z!"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Missing identifier after negation in query"
+        );
+        let result = Input::try_from(vec!["=", "?!!Z"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid identifier in query: '!'"
+        );
+    }
+
+    #[test]
+    fn open_queries() -> Result<()> {
+        let input = Input::try_from(vec!["=", "?~Z~!AZ~Z"])?;
+        assert_eq!(input.queries, "~Z~!AZ");
+        Ok(())
+    }
+
+    #[test]
+    fn error_open_queries() {
+        let result = Input::try_from(vec!["=", "?Z~"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Missing identifier after '~' in query"
+        );
+        let result = Input::try_from(vec!["=", "?!~Z"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid identifier in query: '~'"
+        );
+    }
+
+    #[test]
+    fn add_fact() -> Result<()> {
+        let mut input = Input::new(vec![], "A!B", "Z", Truth::False)?;
+        input.add_fact('C', true)?;
+        input.add_fact('A', true)?;
+        input.add_fact('B', false)?;
+        input.add_fact('D', false)?;
+        assert_eq!(input.facts(), "A!BC!D");
+        Ok(())
+    }
+
+    #[test]
+    fn error_add_fact() -> Result<()> {
+        let mut input = Input::new(vec![], "A!B", "Z", Truth::False)?;
+        let result = input.add_fact('a', true);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid identifier in facts: 'a'"
+        );
+        let result = input.add_fact('B', true);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Fact 'B' is both true and false"
+        );
+        assert_eq!(input.facts(), "A!B");
+        Ok(())
+    }
+
+    #[test]
+    fn add_query() -> Result<()> {
+        let mut input = Input::new(vec![], "A", "Z", Truth::False)?;
+        input.add_query("!Y")?;
+        input.add_query("Z")?;
+        input.add_query("Z|A,B")?;
+        assert_eq!(input.queries(), "Z!YZ|A,B");
+        for (query, message) in [
+            ("YX", "Expected a single query: 'YX'"),
+            ("", "Expected a single query: ''"),
+            ("y", "Invalid identifier in query: 'y'"),
+        ] {
+            let result = input.add_query(query);
+            assert!(result.is_err());
+            assert_eq!(result.unwrap_err().to_string(), message);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn assumptions() -> Result<()> {
+        let input = Input::try_from(vec!["=", "?Z|A,!BYZ|A,!BZ"])?;
+        assert_eq!(input.queries, "Z|A,!BYZ");
+        assert_eq!(
+            split_queries("~!Z|A,BX|CY", OperatorSet::default())?,
+            vec!["~!Z|A,B", "X|C", "Y"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_assumptions() {
+        for (queries, message) in [
+            ("?Z|", "Missing assumption in query"),
+            ("?Z|A,", "Missing assumption in query"),
+            ("?Z|!", "Missing assumption in query"),
+            ("?Z|A,,B", "Invalid assumption in query: ','"),
+            (
+                "?|A",
+                "Invalid identifier in query: '|' (did you mean an operator inside a rule?)",
+            ),
+            ("?Z|A,!A", "Assumption 'A' is both true and false"),
+        ] {
+            let result = Input::try_from(vec!["=", queries]);
+            assert!(result.is_err());
+            assert_eq!(result.unwrap_err().to_string(), message);
+        }
+    }
+
+    #[test]
+    fn set_syntax() -> Result<()> {
+        assert_eq!(
+            Input::try_from(vec!["A=>Z", "={A, B}", "?{Z}"])?,
+            Input::try_from(vec!["A=>Z", "=AB", "?Z"])?
+        );
+        assert_eq!(
+            Input::try_from(vec!["={ }", "?{!Z, Y}"])?,
+            Input::try_from(vec!["=", "?!ZY"])?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_set_syntax() {
+        let result = Input::try_from(vec!["={A", "?Z"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Mismatched braces in facts: '{A'"
+        );
+        let result = Input::try_from(vec!["=A", "?Z}"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Mismatched braces in queries: 'Z}'"
+        );
+        let result = Input::try_from(vec!["=A,B", "?Z"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid identifier in facts: ','"
+        );
+    }
+
+    #[test]
+    fn commented_facts_and_queries() -> Result<()> {
+        assert_eq!(
+            Input::try_from(vec!["A=>Z", "=A B  # initial facts", "?Z # goal"])?,
+            Input::new(vec!["A=>Z".to_string()], "AB", "Z", Truth::False)?
+        );
+        assert_eq!(
+            Input::try_from(vec!["=# no facts", "?!Z#goal"])?,
+            Input::new(vec![], "", "!Z", Truth::False)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn implicator_line_is_rule() -> Result<()> {
+        let input = Input::try_from(vec!["=>Z", "=A", "?Z"])?;
+        assert_eq!(input.rules, vec!["=>Z".to_string()]);
+        assert_eq!(input.facts, "A");
+        Ok(())
+    }
+
+    #[test]
+    fn default_directive() -> Result<()> {
+        let input = Input::try_from(vec!["%default undetermined", "=", "?Z"])?;
+        assert_eq!(input.default, Truth::Undetermined);
+        let input = Input::try_from(vec!["% default false", "=", "?Z"])?;
+        assert_eq!(input.default, Truth::False);
+        Ok(())
+    }
+
+    #[test]
+    fn error_default_directive() {
+        let result = Input::try_from(vec!["%default true", "=", "?Z"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid default value: 'true'"
+        );
+        let result = Input::try_from(vec!["%foo", "=", "?Z"]);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "Unknown directive: '%foo'");
+        let result = Input::try_from(vec!["%default false", "%default false", "=", "?Z"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Multiple default directives found in input file"
+        );
+    }
+
     #[test]
     fn error_empty() {
         let result = Input::try_from(Vec::<String>::new());
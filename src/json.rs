@@ -0,0 +1,135 @@
+use crate::*;
+use input::Input;
+use lint::Warning;
+use rule::Rule;
+use truth::Truth;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Answer {
+    query: String,
+    value: Truth,
+}
+
+// Report is the JSON representation of a run, e.g.
+// `{"answers":[{"query":"Z","value":"true"}],"warnings":[],"contradictions":[]}`. A warning is
+// an object such as `{"kind":"duplicate-rule","message":"rule 'A=>B' is given more than once"}`.
+#[derive(Serialize)]
+struct Report {
+    answers: Vec<Answer>,
+    warnings: Vec<Warning>,
+    contradictions: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Error {
+    error: String,
+}
+
+fn solve(content: &str) -> Result<Report> {
+    let input: Input = content.parse()?;
+    let rules = input
+        .rules()
+        .iter()
+        .map(|r| r.parse())
+        .collect::<Result<Vec<Rule>>>()
+        .context("Failed to parse rule")?;
+    rule::check_labels(&rules)?;
+    let config = solver::SolveConfig::builder()
+        .default_value(input.default())
+        .build();
+    Ok(report(&input, &rules, &config))
+}
+
+fn report(input: &Input, rules: &[Rule], config: &solver::SolveConfig) -> Report {
+    let outcome = solver::run(rules, input.facts(), input.queries(), config);
+    let mut warnings = lint::lint(rules);
+    warnings.extend(outcome.warnings.iter().cloned());
+    Report {
+        answers: outcome
+            .answers
+            .iter()
+            .map(|(query, value)| Answer {
+                query: query.to_string(),
+                value: *value,
+            })
+            .collect(),
+        warnings,
+        contradictions: outcome
+            .contradictions
+            .iter()
+            .map(|c| c.to_string())
+            .collect(),
+    }
+}
+
+// Solves the full text of an input file and returns the answers as JSON. Errors are returned as
+// `{"error":"..."}` holding the whole error chain. Environment variables are not expanded.
+pub fn evaluate(content: &str) -> String {
+    let json = match solve(content) {
+        Ok(report) => serde_json::to_string(&report),
+        Err(e) => serde_json::to_string(&Error {
+            error: format!("{:#}", e),
+        }),
+    };
+    json.expect("Serializing a report can't fail")
+}
+
+// Solves already parsed rules of an input with the given config and returns the answers as JSON,
+// see evaluate.
+pub fn evaluate_rules(input: &Input, rules: &[Rule], config: &solver::SolveConfig) -> String {
+    serde_json::to_string(&report(input, rules, config)).expect("Serializing a report can't fail")
+}
+
+#[cfg(test)]
+mod tests_evaluate {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn simple() {
+        let result = evaluate("A => B\nB => !C\n=A\n?BC!D");
+        assert_eq!(
+            result,
+            concat!(
+                r#"{"answers":[{"query":"B","value":"true"},{"query":"C","value":"false"},"#,
+                r#"{"query":"!D","value":"true"}],"warnings":[],"contradictions":[]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn undetermined() {
+        let result = evaluate("A => B | C\n=A\n?B");
+        assert_eq!(
+            result,
+            r#"{"answers":[{"query":"B","value":"undetermined"}],"warnings":[],"contradictions":[]}"#
+        );
+    }
+
+    #[test]
+    fn warnings() {
+        let result = evaluate("A => B\nA => B\n[1] C => !B\n=AC\n?B");
+        assert_eq!(
+            result,
+            concat!(
+                r#"{"answers":[{"query":"B","value":"false"}],"warnings":["#,
+                r#"{"kind":"duplicate-rule","message":"rule 'A=>B' is given more than once"},"#,
+                r#"{"kind":"rule-override","message":"rule 'C=>!B' overrides rule 'A=>B' for 'B'"}"#,
+                r#"],"contradictions":[]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn error_invalid_rule() {
+        let result = evaluate("A => \n=A\n?B");
+        assert_eq!(
+            result,
+            r#"{"error":"Failed to parse rule: rule 'A =>' is missing a conclusion"}"#
+        );
+    }
+}
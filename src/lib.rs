@@ -1,7 +1,29 @@
+#[cfg(feature = "tokio")]
+pub mod async_api;
+pub mod cancel;
+pub mod cli;
+pub mod engine;
+pub mod format;
+pub mod inference;
 pub mod input;
+pub mod json;
+pub mod lint;
+pub mod node;
+pub mod operator;
 pub mod parser;
 pub mod permutation_iter;
+#[cfg(feature = "profiling")]
+pub mod profile;
+pub mod rule;
 pub mod sanitize;
+pub mod solver;
+pub mod state;
+pub mod symbol_table;
+pub mod truth;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(not(feature = "wasm"))]
+pub mod watch;
 
 use anyhow::{anyhow, Context, Result};
 use core::borrow::Borrow;
@@ -16,10 +38,68 @@ use std::{
 };
 
 pub const USAGE: &str = indoc! {"
-TODO: add usage
+Usage: expert_system [OPTIONS] <INPUT_FILE>
+       expert_system --tokens <FORMULA>
+       expert_system --eval
+
+Options:
+  --quiet                   Don't print the rules, facts and queries of the input
+  --no-tables               Don't build or print the truth tables of the rules
+  --proof                   Print the proof tree of every determined query
+  --why                     Print the rule or fact that determined every query
+  --json                    Print the answers, warnings and contradictions as JSON
+  --stats                   Print the slowest rules to build a truth table for and the depth
+                            and node count of every rule
+  --coverage                Print the rules that never fired
+  --verbose                 Print every rule with its priority and specificity
+  --watch                   Evaluate the input file again whenever it changes
+  --exit-code               Exit with 1 if a query is false, 2 if a query is undetermined, 3 on
+                            a contradiction and 4 on an error
+  --explain-contradiction   Print the derivations of both values of every contradiction
+  --explain-undetermined    Print why every undetermined query couldn't be decided
+  --relevant                Only print the truth tables of rules that affect the queries
+  --as-equivalence          Build the truth tables as if every '=>' were '<=>', solving is not
+                            affected
+  --facts <FACTS_FILE>      Merge the facts of a separate file into the input
+  --checkpoint <FILE>       Write the input with the derived values as facts to this file after
+                            solving
+  --query <QUERY>           Ask this query instead of those of the input, can be repeated
+  --allow-unset             Expand unset environment variables in the input to nothing
+  --mode <MODE>             Solve with all rules (forward) or with the rules relevant to the
+                            queries (backward) [default: forward]
+  --or-policy <POLICY>      Assume nothing (undetermined), the first (first) or every (all)
+                            undecided symbol of a conclusion true [default: undetermined]
+  --max-depth <DEPTH>       Stop solving after this many passes over the rules
+  --max-threads <N>         Build truth tables on at most this many threads
+  --profile <FILE>          Write a flamegraph of the run as SVG, needs the profiling feature
+  --yes                     Build large truth tables without asking for confirmation
+  --strict                  Warn about input that is valid but likely a mistake
+  --abduce <SYMBOL>         Print the minimal sets of facts to assume for the symbol to be true
+  --bool-format <FORMAT>    Render query answers as words, bits or yesno [default: words]
+  --format <FORMAT>         Print the truth tables as text, markdown or csv [default: text]
+  --csv-delimiter <CHAR>    Separate the cells of csv truth tables by this char [default: ,]
+  --no-header               Leave out the header row of csv truth tables
+  --implicator-style <STYLE>
+                            Accept '=>' (fat), '->' (thin) or both as implicator [default: both]
+  --dialect <DIALECT>       Write the operators as '! + | ^' (subject) or '~ & | ^' (c-style)
+                            [default: subject]
+  --tokens <FORMULA>        Only tokenize the formula and print the tokens
+  --eval                    Classify every formula read from stdin, one per line
+  --fmt                     Print the input in canonical form instead of solving it
+  --fmt-write               Write the input in canonical form back to the input file
+  --sort-rules              Sort the rules by conclusion and premise before anything else
+  --expand-bicond           Print the input with every '<=>' rule rewritten into two '=>' rules
+  --count-models            Print the number of assignments satisfying all rules
+  --conclusions             Print every concluded symbol with the rules that can set it
+  --closure                 Print every symbol derived true from the facts with the number of
+                            rule applications it is derived through
+  --dimacs                  Print the consistency of the rules and facts as a problem in DIMACS
+                            CNF format for SAT solvers
+  --consistency             Print whether the rules and facts are free of contradictions
 
 "};
 
+#[allow(clippy::manual_is_ascii_check)]
 pub fn is_identifier(c: impl Borrow<char>) -> bool {
     ('A'..='Z').contains(c.borrow())
 }
@@ -39,6 +119,10 @@ pub fn read_file<T: FromStr>(file: &impl AsRef<Path>) -> Result<Vec<T>> {
     Ok(result)
 }
 
+// Lets the test utilities shared with the integration tests name the crate the same way.
+#[cfg(test)]
+extern crate self as expert_system;
+
 #[cfg(test)]
 #[path = "../tests/test_utils/mod.rs"]
 pub mod test_utils;
@@ -50,6 +134,10 @@ mod tests_is_identifier {
     use pretty_assertions::assert_eq;
 
     #[test]
+    #[allow(
+        clippy::manual_is_ascii_check,
+        clippy::needless_borrows_for_generic_args
+    )]
     fn all() {
         for c in '\0'..=char::MAX {
             assert_eq!(is_identifier(&c), ('A'..='Z').contains(&c));
@@ -68,7 +156,7 @@ mod tests_read_file {
     fn empty() -> Result<()> {
         let input_file = test_utils::input_file_path("read_file/empty.txt");
         let result: Vec<i32> = read_file(&input_file)?;
-        assert_eq!(result, vec![]);
+        assert_eq!(result, Vec::<i32>::new());
         Ok(())
     }
 
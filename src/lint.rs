@@ -0,0 +1,262 @@
+use crate::*;
+use node::{Classification, Node};
+use parser::Direction;
+use rule::Rule;
+
+use core::borrow::Borrow;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// WarningKind is the category of a warning, serialized in kebab-case, e.g. `duplicate-rule`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarningKind {
+    UnsatisfiableConclusion,
+    TautologicalConclusion,
+    DuplicateRule,
+    RuleOverride,
+    UndecidedConclusion,
+    DeepRule,
+}
+
+// Warning is a finding that doesn't prevent solving, as included in the JSON output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, message: String) -> Self {
+        Warning { kind, message }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+// Rules nested deeper than this are warned about, they are hard to read and usually meant to be
+// split into several rules.
+pub const MAX_RULE_DEPTH: usize = 16;
+
+// Checks the rules for mistakes that don't prevent solving, returns a warning for each finding.
+pub fn lint(rules: &[Rule]) -> Vec<Warning> {
+    let mut warnings = vec![];
+    for (i, rule) in rules.iter().enumerate() {
+        // A conclusion with too many symbols to classify is left unchecked.
+        match rule.conclusion.classify() {
+            Ok(Classification::Contradiction) => warnings.push(Warning::new(
+                WarningKind::UnsatisfiableConclusion,
+                format!("rule '{}' has an unsatisfiable conclusion", rule),
+            )),
+            Ok(Classification::Tautology) => warnings.push(Warning::new(
+                WarningKind::TautologicalConclusion,
+                format!("rule '{}' has a tautological conclusion", rule),
+            )),
+            _ => {}
+        }
+        if rule.depth() > MAX_RULE_DEPTH {
+            warnings.push(Warning::new(
+                WarningKind::DeepRule,
+                format!("rule '{}' is nested {} levels deep", rule, rule.depth()),
+            ));
+        }
+        // Only the second occurrence is reported, so a rule given three times warns once.
+        if rules[..i].iter().filter(|r| *r == rule).count() == 1 {
+            warnings.push(Warning::new(
+                WarningKind::DuplicateRule,
+                format!("rule '{}' is given more than once", rule),
+            ));
+        }
+    }
+    warnings
+}
+
+// Returns the messages of the warnings found by lint.
+pub fn lint_rules(rules: &[Rule]) -> Vec<String> {
+    lint(rules).into_iter().map(|w| w.message).collect()
+}
+
+// Checks the lines of an input file before sanitizing for things --strict warns about: a facts
+// line that only holds whitespace or a comment after the `=`, which sanitizes to no facts just like
+// an intentionally empty `=` line.
+pub fn lint_lines(lines: &[impl Borrow<str>]) -> Vec<String> {
+    let mut warnings = vec![];
+    for (number, line) in lines.iter().enumerate() {
+        let line = line.borrow().trim_start();
+        if line.starts_with("=>") {
+            continue;
+        }
+        if let Some(facts) = line.strip_prefix('=') {
+            if !facts.is_empty() && sanitize::sanitize_line(facts).is_empty() {
+                warnings.push(format!("empty facts line on line {}", number + 1));
+            }
+        }
+    }
+    warnings
+}
+
+// Suggests merging pairs of rules that are each other's converse, e.g. `A => B` and `B => A`, into
+// a single biconditional rule. Rules with different priorities are not merged.
+pub fn suggest_merges(rules: &[Rule]) -> Vec<String> {
+    let mut suggestions = vec![];
+    for (i, first) in rules.iter().enumerate() {
+        for second in rules[i + 1..].iter() {
+            if first.direction == Direction::UniDirectional
+                && second.direction == Direction::UniDirectional
+                && first.priority == second.priority
+                && first.premise == second.conclusion
+                && first.conclusion == second.premise
+            {
+                let merged = Node::Implication(
+                    Direction::BiDirectional,
+                    Box::new(first.premise.clone()),
+                    Box::new(first.conclusion.clone()),
+                );
+                suggestions.push(format!(
+                    "rules '{}' and '{}' can be merged into '{}'",
+                    first,
+                    second,
+                    sanitize::remove_spaces(merged.to_string())
+                ));
+            }
+        }
+    }
+    suggestions
+}
+
+#[cfg(test)]
+mod tests_lint_rules {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    fn lint(lines: &[&str]) -> Result<Vec<String>> {
+        let rules: Vec<Rule> = lines.iter().map(|l| l.parse()).collect::<Result<_>>()?;
+        Ok(lint_rules(&rules))
+    }
+
+    #[test]
+    fn unsatisfiable_conclusion() -> Result<()> {
+        assert_eq!(
+            lint(&["A=>B+!B"])?,
+            vec!["rule 'A=>B+!B' has an unsatisfiable conclusion"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tautological_conclusion() -> Result<()> {
+        assert_eq!(
+            lint(&["A=>B|!B"])?,
+            vec!["rule 'A=>B|!B' has a tautological conclusion"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deep_rule() -> Result<()> {
+        // The implication and the symbol add a level each.
+        let rule = format!("{}A=>B", "!".repeat(MAX_RULE_DEPTH - 1));
+        assert_eq!(
+            lint(&[&rule])?,
+            vec![format!("rule '{}' is nested 17 levels deep", rule)]
+        );
+        assert_eq!(lint(&[&rule[1..]])?, Vec::<String>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_rule() -> Result<()> {
+        assert_eq!(
+            lint(&["A=>B", "B=>C", "A=>B", "A=>B"])?,
+            vec!["rule 'A=>B' is given more than once"]
+        );
+        let rules: Vec<Rule> = vec!["A=>B".parse()?, "A=>B".parse()?];
+        assert_eq!(
+            super::lint(&rules),
+            vec![Warning::new(
+                WarningKind::DuplicateRule,
+                "rule 'A=>B' is given more than once".to_string()
+            )]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn normal_conclusion() -> Result<()> {
+        assert_eq!(lint(&["A=>B", "A+B=>!C|D"])?, Vec::<String>::new());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_lint_lines {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn empty_facts() {
+        assert_eq!(lint_lines(&["A=>B", "", "=", "?B"]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn whitespace_facts() {
+        assert_eq!(
+            lint_lines(&["A=>B", "", "=   ", "?B"]),
+            vec!["empty facts line on line 3"]
+        );
+        assert_eq!(
+            lint_lines(&["=\t# no facts"]),
+            vec!["empty facts line on line 1"]
+        );
+    }
+
+    #[test]
+    fn facts() {
+        assert_eq!(
+            lint_lines(&["A=>B", "=A", "= A # fact", "?B"]),
+            Vec::<String>::new()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_suggest_merges {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    fn suggest(lines: &[&str]) -> Result<Vec<String>> {
+        let rules: Vec<Rule> = lines.iter().map(|l| l.parse()).collect::<Result<_>>()?;
+        Ok(suggest_merges(&rules))
+    }
+
+    #[test]
+    fn converse_pair() -> Result<()> {
+        assert_eq!(
+            suggest(&["A=>B", "C=>D", "B=>A"])?,
+            vec!["rules 'A=>B' and 'B=>A' can be merged into 'A<=>B'"]
+        );
+        assert_eq!(
+            suggest(&["(A+B)=>C|D", "C|D=>A+B"])?,
+            vec!["rules '(A+B)=>C|D' and 'C|D=>A+B' can be merged into 'A+B<=>C|D'"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unrelated() -> Result<()> {
+        assert_eq!(
+            suggest(&["A=>B", "B=>C", "A<=>B", "[1]B=>A"])?,
+            Vec::<String>::new()
+        );
+        Ok(())
+    }
+}
@@ -1,29 +1,433 @@
 extern crate expert_system;
+use cancel::CancelFlag;
+use cli::Options;
 use expert_system::*;
-use input::Input;
-use parser::RuleMap;
+use input::{Input, SymbolRole};
+use parser::{RuleMap, RuleParser, TruthTable};
+use rule::Rule;
 
-use anyhow::{Context, Result};
-use std::{env, path::PathBuf};
+use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, IsTerminal};
+use std::{env, fs, process::ExitCode};
 
-fn handle_cli() -> String {
-    let args: Vec<String> = env::args().collect();
-    match args.len() {
-        2 => args[1].clone(),
-        _ => {
+// Number of rules printed by --stats.
+const STATS_SLOWEST_RULES: usize = 5;
+
+// Clears the terminal and moves the cursor to the top left, before every run of --watch.
+#[cfg(not(feature = "wasm"))]
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+// Exit codes of --exit-code, a run where every query is true exits with 0. The highest code that
+// applies is used.
+const EXIT_FALSE: u8 = 1;
+const EXIT_UNDETERMINED: u8 = 2;
+const EXIT_CONTRADICTION: u8 = 3;
+const EXIT_ERROR: u8 = 4;
+
+fn handle_cli() -> Options {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match Options::parse(&args) {
+        Ok(options) => options,
+        Err(_) => {
             eprint!("{}", USAGE);
             std::process::exit(1);
         }
     }
 }
 
-fn main() -> Result<()> {
-    let input_file = handle_cli();
-    let input = Input::try_from(PathBuf::from(input_file)).context("Unable to read input file")?;
+// Asks whether to build a truth table of the given number of rows. Without a terminal to ask on,
+// building is refused so scripts pass --yes instead of hanging.
+fn confirm_rows(rows: u64) -> Result<()> {
+    // A row holds a single bool.
+    eprintln!(
+        "warning: the largest truth table has {} rows and takes about {} MiB",
+        rows,
+        rows >> 20
+    );
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "Refusing to evaluate {} rows without confirmation, pass --yes to continue",
+            rows
+        ));
+    }
+    eprint!("This will evaluate {} rows. Continue? [y/N] ", rows);
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .context("Unable to read confirmation")?;
+    match answer.trim() {
+        "y" | "Y" | "yes" => Ok(()),
+        _ => Err(anyhow!("Aborted")),
+    }
+}
 
-    println!("{:?}", input);
-    let map = RuleMap::try_from(input.rules).context("Failed to parse rule")?;
-    println!("{:?}", map);
+// Returns the exit code of --exit-code for the outcome of a run.
+fn exit_code(outcome: &solver::RunOutcome) -> u8 {
+    if !outcome.contradictions.is_empty() {
+        EXIT_CONTRADICTION
+    } else if outcome
+        .answers
+        .iter()
+        .any(|(_, truth)| *truth == truth::Truth::Undetermined)
+    {
+        EXIT_UNDETERMINED
+    } else if outcome
+        .answers
+        .iter()
+        .any(|(_, truth)| *truth == truth::Truth::False)
+    {
+        EXIT_FALSE
+    } else {
+        0
+    }
+}
 
+fn main() -> Result<ExitCode> {
+    let options = handle_cli();
+    // An interrupt stops building truth tables and solving, the answers found so far are printed.
+    // When watching it stops watching as well.
+    let cancel = CancelFlag::new();
+    #[cfg(not(feature = "wasm"))]
+    {
+        let handler = cancel.clone();
+        ctrlc::set_handler(move || handler.cancel()).context("Unable to set interrupt handler")?;
+        if options.watch {
+            return watch(&options, &cancel);
+        }
+    }
+    match profiled_run(&options, &cancel) {
+        Ok(code) => Ok(ExitCode::from(code)),
+        Err(error) if options.exit_code => {
+            eprintln!("Error: {:?}", error);
+            Ok(ExitCode::from(EXIT_ERROR))
+        }
+        Err(error) => Err(error),
+    }
+}
+
+// Runs with --watch. Errors don't stop watching, the input is evaluated again on the next change.
+#[cfg(not(feature = "wasm"))]
+fn watch(options: &Options, cancel: &CancelFlag) -> Result<ExitCode> {
+    let evaluate = || {
+        print!("{}", CLEAR_SCREEN);
+        if let Err(error) = run(options, cancel) {
+            eprintln!("Error: {:?}", error);
+        }
+    };
+    evaluate();
+    watch::watch(&options.input_file, cancel, evaluate)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+// Runs with --profile, sampling the whole run: parsing, building truth tables and solving.
+#[cfg(feature = "profiling")]
+fn profiled_run(options: &Options, cancel: &CancelFlag) -> Result<u8> {
+    match &options.profile {
+        Some(output) => profile::profile(output, || run(options, cancel))?,
+        None => run(options, cancel),
+    }
+}
+
+#[cfg(not(feature = "profiling"))]
+fn profiled_run(options: &Options, cancel: &CancelFlag) -> Result<u8> {
+    run(options, cancel)
+}
+
+// Classifies every formula read from stdin, see inference::evaluate_formula. An invalid formula is
+// reported without stopping.
+fn eval_stdin() -> Result<()> {
+    for line in io::stdin().lock().lines() {
+        let formula = sanitize::sanitize_line(line.context("Failed to read stdin")?);
+        if formula.is_empty() {
+            continue;
+        }
+        // Printed without spaces like the formula of a rule.
+        let result = inference::evaluate_formula(&formula);
+        let formula = sanitize::remove_spaces(formula);
+        match result {
+            Ok(evaluation) => println!("{}: {}", formula, evaluation),
+            Err(error) => eprintln!("error: {}: {:#}", formula, error),
+        }
+    }
     Ok(())
 }
+
+// Returns the exit code of the run, which is 0 unless --exit-code is given.
+fn run(options: &Options, cancel: &CancelFlag) -> Result<u8> {
+    if options.eval {
+        eval_stdin()?;
+        return Ok(0);
+    }
+    if let Some(formula) = &options.tokens {
+        let tokens = RuleParser::new()
+            .with_operators(options.dialect)
+            .tokenize(formula)
+            .context(format!("Failed to tokenize input: '{}'", formula))?;
+        println!("{}", parser::dump_tokens(&tokens));
+        return Ok(0);
+    }
+    if options.strict {
+        let content =
+            fs::read_to_string(&options.input_file).context("Unable to read input file")?;
+        for warning in lint::lint_lines(&content.lines().collect::<Vec<&str>>()) {
+            eprintln!("warning: {}", warning);
+        }
+    }
+    let mut input = match &options.facts_file {
+        Some(facts_file) => {
+            let (input, duplicates) = Input::load_with_facts(
+                &options.input_file,
+                facts_file,
+                options.allow_unset,
+                options.dialect,
+            )
+            .context("Unable to read input file")?;
+            for c in duplicates {
+                eprintln!(
+                    "warning: fact '{}' is given in both the input file and the facts file",
+                    c
+                );
+            }
+            input
+        }
+        None => Input::load(&options.input_file, options.allow_unset, options.dialect)
+            .context("Unable to read input file")?,
+    };
+    if options.dialect != operator::OperatorSet::default() {
+        input.translate_operators(options.dialect)?;
+    }
+    if !options.queries.is_empty() {
+        input
+            .set_queries(&options.queries)
+            .context("Invalid query option")?;
+    }
+    if options.strict {
+        for (symbol, roles) in input.symbol_roles()? {
+            if roles == BTreeSet::from([SymbolRole::QueryOnly]) {
+                eprintln!(
+                    "warning: query '{}' is about a symbol no rule or fact mentions",
+                    symbol
+                );
+            }
+        }
+    }
+    if options.sort_rules {
+        input.sort_rules()?;
+    }
+
+    if options.expand_bicond {
+        print!("{}", format::expand_input(&input)?);
+        return Ok(0);
+    }
+    if options.fmt || options.fmt_write {
+        let formatted = format::format_input(&input)?;
+        if options.fmt_write {
+            fs::write(&options.input_file, formatted).context("Unable to write input file")?;
+        } else {
+            print!("{}", formatted);
+        }
+        return Ok(0);
+    }
+
+    if !options.quiet && !options.json {
+        println!("{}", input.summary()?);
+        println!("{:?}", input);
+    }
+    let mut rules = input
+        .rules()
+        .iter()
+        .map(|r| Rule::parse_with_style(r, options.implicator_style))
+        .collect::<Result<Vec<Rule>>>()
+        .context("Failed to parse rule")?;
+    rule::check_labels(&rules)?;
+    if options.verbose && !options.json {
+        for rule in rules.iter() {
+            println!(
+                "rule '{}': priority {}, specificity {}",
+                rule,
+                rule.priority,
+                rule.specificity()
+            );
+        }
+    }
+    if options.count_models {
+        println!("{} models", inference::count_models(&rules)?);
+        return Ok(0);
+    }
+    if options.conclusions {
+        let engine = engine::Engine::new(rules, state::State::default());
+        for (symbol, group) in engine.conclusion_groups() {
+            let group: Vec<String> = group.iter().map(|r| format!("'{}'", r)).collect();
+            println!("{} is concluded by {}", symbol, group.join(", "));
+        }
+        return Ok(0);
+    }
+    if options.closure {
+        let levels: Vec<String> = solver::closure(&rules, input.facts())
+            .iter()
+            .map(|(symbol, level)| format!("{}:{}", symbol, level))
+            .collect();
+        println!("{}", levels.join(", "));
+        return Ok(0);
+    }
+    if options.dimacs {
+        print!("{}", inference::to_dimacs(&rules, input.facts())?);
+        return Ok(0);
+    }
+    if options.consistency {
+        match inference::is_consistent(&rules, input.facts()) {
+            Ok(()) => println!("consistent"),
+            Err(contradiction) => println!("{}", contradiction),
+        }
+        return Ok(0);
+    }
+    let mut config = solver::SolveConfig::builder()
+        .cancel(cancel.clone())
+        .mode(options.mode)
+        .or_policy(options.or_policy)
+        .default_value(input.default());
+    if let Some(max_depth) = options.max_depth {
+        config = config.max_depth(max_depth);
+    }
+    let config = config.build();
+    if options.json {
+        println!("{}", json::evaluate_rules(&input, &rules, &config));
+        return Ok(0);
+    }
+    if let Some(seed) = options.shuffle_seed {
+        rule::shuffle_rules(&mut rules, seed);
+    }
+    for warning in lint::lint_rules(&rules) {
+        eprintln!("warning: {}", warning);
+    }
+    for suggestion in lint::suggest_merges(&rules) {
+        eprintln!("info: {}", suggestion);
+    }
+    let printed: Vec<&Rule> = if options.relevant {
+        rule::relevant_rules(&rules, input.queries())
+    } else {
+        rules.iter().collect()
+    };
+    let formulas: Vec<String> = printed
+        .iter()
+        .map(|r| {
+            if options.as_equivalence {
+                r.as_equivalence()
+            } else {
+                r.formula.clone()
+            }
+        })
+        .collect();
+    // Solving doesn't use the truth tables, so --no-tables skips building them.
+    if !options.no_tables {
+        if let Some(rows) = parser::rows_to_confirm(&formulas, parser::ROW_LIMIT, options.yes)? {
+            confirm_rows(rows)?;
+        }
+        #[cfg(not(feature = "wasm"))]
+        let map =
+            RuleMap::try_from_parallel(formulas, parser::thread_count(options.max_threads), cancel);
+        // The wasm build has no threads to build on, so --max-threads is ignored there.
+        #[cfg(feature = "wasm")]
+        let map = RuleMap::try_from_cancellable(formulas, cancel);
+        let map = map.context("Failed to parse rule")?;
+        println!("{}", map.render(options.table_format));
+        if options.stats {
+            let mut timings = map.timings().to_vec();
+            timings.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+            for (rule, duration) in timings.iter().take(STATS_SLOWEST_RULES) {
+                eprintln!("stats: rule '{}' took {:?}", rule, duration);
+            }
+        }
+    }
+    for expression in input.tables() {
+        let table = TruthTable::from_expression(expression)?;
+        println!("{}\n{}", expression, options.table_format.render(&table)?);
+    }
+    if options.stats {
+        for rule in rules.iter() {
+            eprintln!(
+                "stats: rule '{}' has depth {} and {} nodes",
+                rule,
+                rule.depth(),
+                rule.node_count()
+            );
+        }
+    }
+
+    let outcome = solver::run(&rules, input.facts(), input.queries(), &config);
+    for warning in outcome.warnings.iter() {
+        eprintln!("warning: {}", warning);
+    }
+    if outcome.cancelled {
+        eprintln!("warning: interrupted, the answers are partial");
+    }
+    if options.explain_contradiction {
+        for trace in outcome.traces.iter() {
+            eprint!("error: {}", trace);
+        }
+    } else {
+        for contradiction in outcome.contradictions.iter() {
+            eprintln!("error: {}", contradiction);
+        }
+    }
+    for (query, truth) in outcome.answers.iter() {
+        let answer = options.bool_format.render(*truth);
+        match outcome.justifications.get(query) {
+            Some(justification) if options.why => {
+                println!("{} is {} ({})", query, answer, justification.clause())
+            }
+            _ => println!("{} is {}", query, answer),
+        }
+        if let Some(explanation) = outcome.explanations.get(query).filter(|_| options.why) {
+            println!("  {}", explanation);
+        }
+    }
+    if let Some(checkpoint) = &options.checkpoint {
+        fs::write(
+            checkpoint,
+            format::checkpoint_input(&input, &outcome.derived_facts)?,
+        )
+        .context("Unable to write checkpoint")?;
+    }
+    if options.coverage {
+        for rule in outcome.unfired_rules.iter() {
+            println!("rule '{}' never fired", rule);
+        }
+    }
+    if options.proof {
+        for query in solver::Query::parse_all(input.queries()) {
+            if let Some(proof) = outcome.proofs.get(&query) {
+                print!("{}", proof);
+            }
+        }
+    }
+    if options.explain_undetermined {
+        for query in solver::Query::parse_all(input.queries()) {
+            if let Some(explanation) =
+                solver::explain_undetermined(&rules, input.facts(), input.default(), query.symbol)
+            {
+                println!("{}", explanation);
+            }
+        }
+    }
+    if let Some(query) = options.abduce {
+        for assumptions in solver::abduce(&rules, input.facts(), query) {
+            if assumptions.is_empty() {
+                println!("{} is true without assumptions", query);
+            } else {
+                println!(
+                    "{} is true when assuming {}",
+                    query,
+                    assumptions.iter().collect::<String>()
+                );
+            }
+        }
+    }
+
+    Ok(if options.exit_code {
+        exit_code(&outcome)
+    } else {
+        0
+    })
+}
@@ -0,0 +1,716 @@
+use crate::*;
+use operator::Operator;
+use parser::Direction;
+use state::State;
+use truth::Truth;
+
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+// Valuation gives the value of every symbol a tree is evaluated against, so a tree can be evaluated
+// against a map or directly against a State without building a map first.
+pub trait Valuation {
+    // Returns the value of the symbol, undetermined when it has none.
+    fn truth(&self, symbol: char) -> Truth;
+}
+
+impl Valuation for HashMap<char, bool> {
+    fn truth(&self, symbol: char) -> Truth {
+        self.get(&symbol)
+            .map_or(Truth::Undetermined, |b| Truth::from(*b))
+    }
+}
+
+impl Valuation for HashMap<char, Truth> {
+    fn truth(&self, symbol: char) -> Truth {
+        self.get(&symbol).copied().unwrap_or(Truth::Undetermined)
+    }
+}
+
+impl Valuation for State {
+    fn truth(&self, symbol: char) -> Truth {
+        self.get(symbol).map_or(Truth::Undetermined, Truth::from)
+    }
+}
+
+// Classification of a formula over all assignments of its identifiers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Classification {
+    Tautology,
+    Contradiction,
+    Contingent,
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Classification::Tautology => write!(f, "tautology"),
+            Classification::Contradiction => write!(f, "contradiction"),
+            Classification::Contingent => write!(f, "contingent"),
+        }
+    }
+}
+
+// Literal is an identifier or its negation, as found in the clauses of Node::to_cnf.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Literal {
+    pub symbol: char,
+    pub negated: bool,
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negated {
+            write!(f, "!")?;
+        }
+        write!(f, "{}", self.symbol)
+    }
+}
+
+// The largest number of clauses Node::to_cnf builds. Distributing `|` over `+` can double the
+// clauses with every `|`, which is only acceptable for small formulas.
+pub const MAX_CLAUSES: usize = 1 << 16;
+
+// Node is the syntax tree of a rule as produced by the RuleParser. Operators of equal precedence
+// are grouped from left to right, so `A + B | C` is represented as:
+// `Binary('|', Binary('+', A, B), C)`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Node {
+    Bool(bool),
+    Identifier(char),
+    Not(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    Implication(Direction, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    // Evaluates the tree, every identifier in the tree is expected to have a value in the state.
+    pub fn eval(&self, state: &impl Valuation) -> Result<bool> {
+        match self {
+            Node::Bool(b) => Ok(*b),
+            Node::Identifier(c) => match state.truth(*c) {
+                Truth::True => Ok(true),
+                Truth::False => Ok(false),
+                Truth::Undetermined => Err(anyhow!("Unassigned identifier '{}'", c)),
+            },
+            Node::Not(node) => Ok(!node.eval(state)?),
+            Node::Binary(op, lhs, rhs) => Ok(op.apply(lhs.eval(state)?, rhs.eval(state)?)),
+            // A false premise makes an implication true without evaluating the conclusion.
+            Node::Implication(Direction::UniDirectional, lhs, rhs) => match lhs.eval(state)? {
+                false => Ok(true),
+                true => rhs.eval(state),
+            },
+            Node::Implication(Direction::BiDirectional, lhs, rhs) => {
+                Ok(lhs.eval(state)? == rhs.eval(state)?)
+            }
+        }
+    }
+
+    // Evaluates the tree using Kleene's three-valued logic, identifiers missing from the state are
+    // undetermined. The operators are those of Truth, so `1 | Undetermined` is true but
+    // `1 + Undetermined` is undetermined.
+    pub fn eval3(&self, state: &impl Valuation) -> Truth {
+        match self {
+            Node::Bool(b) => Truth::from(*b),
+            Node::Identifier(c) => state.truth(*c),
+            Node::Not(node) => !node.eval3(state),
+            Node::Binary(op, lhs, rhs) => op.apply3(lhs.eval3(state), rhs.eval3(state)),
+            Node::Implication(direction, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval3(state), rhs.eval3(state));
+                match direction {
+                    Direction::UniDirectional => lhs.implies(rhs),
+                    Direction::BiDirectional => lhs.iff(rhs),
+                }
+            }
+        }
+    }
+
+    // Classifies the tree by evaluating it for every assignment of its identifiers, fails like
+    // count_true.
+    pub fn classify(&self) -> Result<Classification> {
+        Ok(match self.count_true()? {
+            (0, _) => Classification::Contradiction,
+            (true_rows, rows) if true_rows == rows => Classification::Tautology,
+            _ => Classification::Contingent,
+        })
+    }
+
+    // Returns for how many assignments of its identifiers the tree is true, and the number of
+    // assignments. An assignment that fails to evaluate counts as false.
+    pub fn count_true(&self) -> Result<(usize, usize)> {
+        let symbols: Vec<char> = self.symbols().into_iter().collect();
+        let rows = permutation_iter::permutation_count(symbols.len())?;
+        let mut state = HashMap::new();
+        let mut true_rows = 0;
+        for permutation in 0..rows {
+            for (i, c) in symbols.iter().enumerate() {
+                state.insert(*c, permutation & (1 << i) != 0);
+            }
+            if let Ok(true) = self.eval(&state) {
+                true_rows += 1;
+            }
+        }
+        Ok((true_rows, rows))
+    }
+
+    // Returns all identifiers in the tree in sorted order.
+    pub fn symbols(&self) -> BTreeSet<char> {
+        let mut symbols = BTreeSet::new();
+        self.collect_symbols(&mut symbols);
+        symbols
+    }
+
+    // Returns all identifiers in the tree in order of their first appearance, e.g. `B` before `A`
+    // for `B | A`.
+    pub fn symbols_in_order(&self) -> Vec<char> {
+        let mut symbols = vec![];
+        self.collect_symbols_in_order(&mut symbols);
+        symbols
+    }
+
+    fn collect_symbols_in_order(&self, symbols: &mut Vec<char>) {
+        match self {
+            Node::Bool(_) => {}
+            Node::Identifier(c) => {
+                if !symbols.contains(c) {
+                    symbols.push(*c);
+                }
+            }
+            Node::Not(node) => node.collect_symbols_in_order(symbols),
+            Node::Binary(_, lhs, rhs) | Node::Implication(_, lhs, rhs) => {
+                lhs.collect_symbols_in_order(symbols);
+                rhs.collect_symbols_in_order(symbols);
+            }
+        }
+    }
+
+    // Returns the tree in negation normal form: only `+` and `|` remain and every `!` applies to
+    // a single identifier, e.g. `!A | !B` for `!(A + B)`. Xor and implications are first rewritten
+    // into `+` and `|`, e.g. `!A | B` for `A => B`. The result has the same truth table.
+    pub fn to_nnf(&self) -> Node {
+        self.nnf(false)
+    }
+
+    // Returns the negation normal form of the tree, or of its negation when negated.
+    fn nnf(&self, negated: bool) -> Node {
+        let binary = |op: char, lhs: Node, rhs: Node| {
+            Node::Binary(
+                Operator::new(op).expect("Valid operator"),
+                Box::new(lhs),
+                Box::new(rhs),
+            )
+        };
+        // Either both sides hold or neither, or exactly one side when negated.
+        let equal = |lhs: &Node, rhs: &Node, negated: bool| {
+            binary(
+                '|',
+                binary('+', lhs.nnf(false), rhs.nnf(negated)),
+                binary('+', lhs.nnf(true), rhs.nnf(!negated)),
+            )
+        };
+        match self {
+            Node::Bool(b) => Node::Bool(*b != negated),
+            Node::Identifier(_) if negated => Node::Not(Box::new(self.clone())),
+            Node::Identifier(_) => self.clone(),
+            Node::Not(node) => node.nnf(!negated),
+            Node::Binary(op, lhs, rhs) => match (op.symbol(), negated) {
+                ('+', false) | ('|', true) => binary('+', lhs.nnf(negated), rhs.nnf(negated)),
+                ('|', false) | ('+', true) => binary('|', lhs.nnf(negated), rhs.nnf(negated)),
+                _ => equal(lhs, rhs, !negated),
+            },
+            Node::Implication(Direction::UniDirectional, lhs, rhs) if negated => {
+                binary('+', lhs.nnf(false), rhs.nnf(true))
+            }
+            Node::Implication(Direction::UniDirectional, lhs, rhs) => {
+                binary('|', lhs.nnf(true), rhs.nnf(false))
+            }
+            Node::Implication(Direction::BiDirectional, lhs, rhs) => equal(lhs, rhs, negated),
+        }
+    }
+
+    // Returns the tree in conjunctive normal form: the clauses that all have to hold, each holding
+    // when any of its literals does. Built from the negation normal form by distributing `|` over
+    // `+`, e.g. `[[!A, B]]` for `A => B`. Every clause holds each literal once and clauses that
+    // always hold are left out, so a tautology has no clauses and a contradiction has an empty
+    // clause. Fails when the result would exceed MAX_CLAUSES.
+    pub fn to_cnf(&self) -> Result<Vec<Vec<Literal>>> {
+        let clauses = self.to_nnf().cnf()?;
+        if clauses.len() > MAX_CLAUSES {
+            return Err(anyhow!("CNF of '{}' exceeds {} clauses", self, MAX_CLAUSES));
+        }
+        Ok(clauses)
+    }
+
+    // Returns the literals of the negation normal form of the tree in order, e.g. `!A` and `B` for
+    // `!(A + !B)`. A literal is repeated when its identifier is.
+    pub fn literals(&self) -> Vec<Literal> {
+        let mut literals = vec![];
+        self.to_nnf().collect_literals(&mut literals);
+        literals
+    }
+
+    fn collect_literals(&self, literals: &mut Vec<Literal>) {
+        match self {
+            Node::Bool(_) => {}
+            Node::Identifier(c) => literals.push(Literal {
+                symbol: *c,
+                negated: false,
+            }),
+            Node::Not(node) => {
+                if let Node::Identifier(c) = **node {
+                    literals.push(Literal {
+                        symbol: c,
+                        negated: true,
+                    });
+                }
+            }
+            Node::Binary(_, lhs, rhs) | Node::Implication(_, lhs, rhs) => {
+                lhs.collect_literals(literals);
+                rhs.collect_literals(literals);
+            }
+        }
+    }
+
+    // Returns the clauses of a tree in negation normal form.
+    fn cnf(&self) -> Result<Vec<Vec<Literal>>> {
+        let literal = |node: &Node, negated: bool| match node {
+            Node::Identifier(c) => Ok(Literal {
+                symbol: *c,
+                negated,
+            }),
+            _ => Err(anyhow!("Not in negation normal form: '{}'", node)),
+        };
+        match self {
+            Node::Bool(true) => Ok(vec![]),
+            Node::Bool(false) => Ok(vec![vec![]]),
+            Node::Identifier(_) => Ok(vec![vec![literal(self, false)?]]),
+            Node::Not(node) => Ok(vec![vec![literal(node, true)?]]),
+            Node::Binary(op, lhs, rhs) if op.symbol() == '+' => {
+                let mut clauses = lhs.cnf()?;
+                clauses.append(&mut rhs.cnf()?);
+                Ok(clauses)
+            }
+            Node::Binary(op, lhs, rhs) if op.symbol() == '|' => {
+                let (lhs, rhs) = (lhs.cnf()?, rhs.cnf()?);
+                if lhs.len() * rhs.len() > MAX_CLAUSES {
+                    return Err(anyhow!("CNF of '{}' exceeds {} clauses", self, MAX_CLAUSES));
+                }
+                let mut clauses = vec![];
+                for l in lhs.iter() {
+                    for r in rhs.iter() {
+                        let mut clause = l.clone();
+                        clause.extend(r.iter().filter(|literal| !l.contains(literal)));
+                        if !clause.iter().any(|a| {
+                            clause
+                                .iter()
+                                .any(|b| a.symbol == b.symbol && a.negated != b.negated)
+                        }) {
+                            clauses.push(clause);
+                        }
+                    }
+                }
+                Ok(clauses)
+            }
+            _ => Err(anyhow!("Not in negation normal form: '{}'", self)),
+        }
+    }
+
+    // Returns the height of the tree, a single symbol has depth 1.
+    pub fn depth(&self) -> usize {
+        match self {
+            Node::Bool(_) | Node::Identifier(_) => 1,
+            Node::Not(node) => 1 + node.depth(),
+            Node::Binary(_, lhs, rhs) | Node::Implication(_, lhs, rhs) => {
+                1 + lhs.depth().max(rhs.depth())
+            }
+        }
+    }
+
+    // Returns the number of nodes in the tree, operators and operands alike.
+    pub fn node_count(&self) -> usize {
+        match self {
+            Node::Bool(_) | Node::Identifier(_) => 1,
+            Node::Not(node) => 1 + node.node_count(),
+            Node::Binary(_, lhs, rhs) | Node::Implication(_, lhs, rhs) => {
+                1 + lhs.node_count() + rhs.node_count()
+            }
+        }
+    }
+
+    fn collect_symbols(&self, symbols: &mut BTreeSet<char>) {
+        match self {
+            Node::Bool(_) => {}
+            Node::Identifier(c) => {
+                symbols.insert(*c);
+            }
+            Node::Not(node) => node.collect_symbols(symbols),
+            Node::Binary(_, lhs, rhs) | Node::Implication(_, lhs, rhs) => {
+                lhs.collect_symbols(symbols);
+                rhs.collect_symbols(symbols);
+            }
+        }
+    }
+}
+
+// Displays the tree in canonical form, e.g. `A + !(B | C) => D`. Parentheses are only added where
+// the left to right grouping of the parser requires them, so parsing the output yields the same tree.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Node::Bool(b) => write!(f, "{}", *b as u8),
+            Node::Identifier(c) => write!(f, "{}", c),
+            Node::Not(node) => match **node {
+                Node::Binary(..) | Node::Implication(..) => write!(f, "!({})", node),
+                _ => write!(f, "!{}", node),
+            },
+            Node::Binary(op, lhs, rhs) => match **rhs {
+                Node::Binary(..) | Node::Implication(..) => write!(f, "{} {} ({})", lhs, op, rhs),
+                _ => write!(f, "{} {} {}", lhs, op, rhs),
+            },
+            Node::Implication(direction, lhs, rhs) => match direction {
+                Direction::UniDirectional => write!(f, "{} => {}", lhs, rhs),
+                Direction::BiDirectional => write!(f, "{} <=> {}", lhs, rhs),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_node {
+    use super::*;
+    use parser::RuleParser;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn symbols_in_order() -> Result<()> {
+        let node = RuleParser::new().parse("C | (A + C) => B | !A")?;
+        assert_eq!(node.symbols_in_order(), vec!['C', 'A', 'B']);
+        Ok(())
+    }
+
+    #[test]
+    fn to_nnf() -> Result<()> {
+        let nnf = |formula: &str| -> Result<String> {
+            Ok(RuleParser::new().parse(formula)?.to_nnf().to_string())
+        };
+        assert_eq!(nnf("1 => !(A + B)")?, "0 | (!A | !B)");
+        assert_eq!(nnf("1 => !(A | B)")?, "0 | (!A + !B)");
+        assert_eq!(nnf("1 => !!A")?, "0 | A");
+        Ok(())
+    }
+
+    #[test]
+    fn to_nnf_equivalent() -> Result<()> {
+        for formula in [
+            "A + B => C",
+            "!(A + !B) | C => !D",
+            "!(A ^ B) => !(C ^ !D)",
+            "A + B <=> !(C | D)",
+            "!!(A | !(B + 1)) => 0",
+        ] {
+            let node = RuleParser::new().parse(formula)?;
+            let nnf = node.to_nnf();
+            let equivalent = Node::Implication(
+                Direction::BiDirectional,
+                Box::new(node.clone()),
+                Box::new(nnf.clone()),
+            );
+            assert_eq!(
+                equivalent.classify()?,
+                Classification::Tautology,
+                "{}",
+                formula
+            );
+            assert_no_inner_not(&nnf);
+        }
+        Ok(())
+    }
+
+    // Asserts that every `!` of the tree applies to an identifier and that only `+` and `|` are
+    // left.
+    fn assert_no_inner_not(node: &Node) {
+        match node {
+            Node::Bool(_) | Node::Identifier(_) => {}
+            Node::Not(node) => assert!(matches!(**node, Node::Identifier(_)), "{}", node),
+            Node::Binary(op, lhs, rhs) => {
+                assert!(['+', '|'].contains(&op.symbol()), "{}", op);
+                assert_no_inner_not(lhs);
+                assert_no_inner_not(rhs);
+            }
+            Node::Implication(..) => panic!("Implication in negation normal form: {}", node),
+        }
+    }
+
+    #[test]
+    fn to_cnf() -> Result<()> {
+        let cnf = |formula: &str| -> Result<Vec<Vec<Literal>>> {
+            RuleParser::new().parse(formula)?.to_cnf()
+        };
+        let literal = |symbol: char, negated: bool| Literal { symbol, negated };
+        assert_eq!(
+            cnf("A => B")?,
+            vec![vec![literal('A', true), literal('B', false)]]
+        );
+        assert_eq!(
+            cnf("A => B + C")?,
+            vec![
+                vec![literal('A', true), literal('B', false)],
+                vec![literal('A', true), literal('C', false)]
+            ]
+        );
+        assert_eq!(cnf("A => A")?, Vec::<Vec<Literal>>::new());
+        assert_eq!(cnf("1 => 0")?, vec![vec![]]);
+        Ok(())
+    }
+
+    #[test]
+    fn error_to_cnf_size() -> Result<()> {
+        // Every `|` of conjunctions doubles the clauses, 17 of them give 2^17 clauses.
+        let formula = (0..17u8)
+            .map(|i| {
+                let c = |offset: u8| char::from(b'A' + (2 * i + offset) % 26);
+                format!("({} + {})", c(0), c(1))
+            })
+            .collect::<Vec<String>>()
+            .join(" | ");
+        let result = RuleParser::new()
+            .parse(&format!("1 => {}", formula))?
+            .to_cnf();
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .ends_with("exceeds 65536 clauses"));
+        Ok(())
+    }
+
+    #[test]
+    fn literals() -> Result<()> {
+        let node = RuleParser::new().parse("1 => !(A + !B) | A")?;
+        let literal = |symbol: char, negated: bool| Literal { symbol, negated };
+        assert_eq!(
+            node.literals(),
+            vec![literal('A', true), literal('B', false), literal('A', false)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn depth() -> Result<()> {
+        let node = RuleParser::new().parse("(A + B) | !C => Z")?;
+        match &node {
+            Node::Implication(_, premise, _) => assert_eq!(premise.depth(), 3),
+            _ => unreachable!(),
+        }
+        assert_eq!(node.depth(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn node_count() -> Result<()> {
+        let node = RuleParser::new().parse("(A + B) | !C => Z")?;
+        match &node {
+            Node::Implication(_, premise, _) => assert_eq!(premise.node_count(), 6),
+            _ => unreachable!(),
+        }
+        assert_eq!(node.node_count(), 8);
+        Ok(())
+    }
+
+    #[test]
+    fn left_grouping() -> Result<()> {
+        let node = RuleParser::new().parse("A + B | C => Z")?;
+        let expected = Node::Implication(
+            Direction::UniDirectional,
+            Box::new(Node::Binary(
+                Operator::new('|')?,
+                Box::new(Node::Binary(
+                    Operator::new('+')?,
+                    Box::new(Node::Identifier('A')),
+                    Box::new(Node::Identifier('B')),
+                )),
+                Box::new(Node::Identifier('C')),
+            )),
+            Box::new(Node::Identifier('Z')),
+        );
+        assert_eq!(node, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn eval() -> Result<()> {
+        let node = RuleParser::new().parse("A + !B => Z")?;
+        let state = HashMap::from([('A', true), ('B', false), ('Z', false)]);
+        assert_eq!(node.eval(&state)?, false);
+        let state = HashMap::from([('A', true), ('B', true), ('Z', false)]);
+        assert_eq!(node.eval(&state)?, true);
+        Ok(())
+    }
+
+    #[test]
+    fn eval_implication() -> Result<()> {
+        // The conclusion of a false premise isn't evaluated, so its symbols needn't be assigned.
+        let node = RuleParser::new().parse("A => B + (C | D)")?;
+        assert_eq!(node.eval(&HashMap::from([('A', false)]))?, true);
+        for b in [false, true] {
+            let state = HashMap::from([('A', true), ('B', b), ('C', true), ('D', false)]);
+            assert_eq!(node.eval(&state)?, b);
+        }
+        let node = RuleParser::new().parse("0 => Z")?;
+        assert_eq!(node.eval(&State::default())?, true);
+        let node = RuleParser::new().parse("A <=> Z")?;
+        assert!(node.eval(&HashMap::from([('A', false)])).is_err());
+        Ok(())
+    }
+
+    fn kleene(formula: &str) -> Result<Vec<Truth>> {
+        let node = RuleParser::new().parse(&format!("{} => 1", formula))?;
+        let Node::Implication(_, node, _) = node else {
+            unreachable!()
+        };
+        let values = [Truth::True, Truth::False, Truth::Undetermined];
+        let mut result = vec![];
+        for a in values {
+            for b in values {
+                result.push(node.eval3(&HashMap::from([('A', a), ('B', b)])));
+            }
+        }
+        Ok(result)
+    }
+
+    #[test]
+    fn eval3_and() -> Result<()> {
+        use Truth::*;
+        assert_eq!(
+            kleene("A + B")?,
+            vec![
+                True,
+                False,
+                Undetermined,
+                False,
+                False,
+                False,
+                Undetermined,
+                False,
+                Undetermined
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn eval3_or() -> Result<()> {
+        use Truth::*;
+        assert_eq!(
+            kleene("A | B")?,
+            vec![
+                True,
+                True,
+                True,
+                True,
+                False,
+                Undetermined,
+                True,
+                Undetermined,
+                Undetermined
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn eval3_xor() -> Result<()> {
+        use Truth::*;
+        assert_eq!(
+            kleene("A ^ B")?,
+            vec![
+                False,
+                True,
+                Undetermined,
+                True,
+                False,
+                Undetermined,
+                Undetermined,
+                Undetermined,
+                Undetermined
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn eval3_not() -> Result<()> {
+        use Truth::*;
+        let node = RuleParser::new().parse("!A => 1")?;
+        let Node::Implication(_, node, _) = node else {
+            unreachable!()
+        };
+        assert_eq!(node.eval3(&HashMap::from([('A', True)])), False);
+        assert_eq!(node.eval3(&HashMap::from([('A', False)])), True);
+        assert_eq!(
+            node.eval3(&HashMap::from([('A', Undetermined)])),
+            Undetermined
+        );
+        assert_eq!(node.eval3(&State::default()), Undetermined);
+        Ok(())
+    }
+
+    #[test]
+    fn eval3_implication() -> Result<()> {
+        let node = RuleParser::new().parse("A => B")?;
+        let state = HashMap::from([('A', Truth::False)]);
+        assert_eq!(node.eval3(&state), Truth::True);
+        let state = HashMap::from([('A', Truth::True)]);
+        assert_eq!(node.eval3(&state), Truth::Undetermined);
+        let node = RuleParser::new().parse("A <=> B")?;
+        let state = HashMap::from([('A', Truth::False), ('B', Truth::False)]);
+        assert_eq!(node.eval3(&state), Truth::True);
+        Ok(())
+    }
+
+    #[test]
+    fn classify() -> Result<()> {
+        let classify = |formula: &str| -> Result<Classification> {
+            RuleParser::new().parse(formula)?.classify()
+        };
+        assert_eq!(classify("A => A")?, Classification::Tautology);
+        assert_eq!(classify("A => !A")?, Classification::Contingent);
+        assert_eq!(classify("A + !A <=> 1")?, Classification::Contradiction);
+        assert_eq!(classify("1 => 1")?, Classification::Tautology);
+        Ok(())
+    }
+
+    #[test]
+    fn display() -> Result<()> {
+        for (formula, expected) in [
+            ("A+B=>C", "A + B => C"),
+            ("(A+B)|C<=>!D", "A + B | C <=> !D"),
+            ("A+(B|C)=>!(D^1)", "A + (B | C) => !(D ^ 1)"),
+            ("!!A=>((B))", "!!A => B"),
+        ] {
+            let node = RuleParser::new().parse(formula)?;
+            assert_eq!(node.to_string(), expected);
+            assert_eq!(RuleParser::new().parse(&node.to_string())?, node);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn symbols() -> Result<()> {
+        let node = RuleParser::new().parse("C + A => B | A")?;
+        assert_eq!(node.symbols(), BTreeSet::from(['A', 'B', 'C']));
+        Ok(())
+    }
+
+    #[test]
+    fn error_unassigned() -> Result<()> {
+        let node = RuleParser::new().parse("A => Z")?;
+        let result = node.eval(&HashMap::from([('A', true)]));
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "Unassigned identifier 'Z'");
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use crate::truth::Truth;
+
+use anyhow::{anyhow, Result};
+use std::fmt;
+use std::str::FromStr;
+
+// Operator is one of the logical operators of the rule syntax: `!` (not), `+` (and), `|` (or) and
+// `^` (xor). It can only be constructed from one of these chars, so evaluation never has to deal
+// with an unknown operator.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Operator(char);
+
+impl Operator {
+    pub fn new(c: char) -> Result<Self> {
+        match c {
+            '!' | '+' | '|' | '^' => Ok(Operator(c)),
+            _ => Err(anyhow!("Invalid operator '{}'", c)),
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        self.0
+    }
+
+    pub fn is_unary(&self) -> bool {
+        self.0 == '!'
+    }
+
+    // Applies the binary operator to both operands, the unary `!` only negates the first one.
+    pub fn apply(&self, a: bool, b: bool) -> bool {
+        match self.0 {
+            '+' => a & b,
+            '|' => a | b,
+            '^' => a ^ b,
+            '!' => !a,
+            _ => unreachable!(),
+        }
+    }
+
+    // Applies the operator like apply, in Kleene's three-valued logic.
+    pub fn apply3(&self, a: Truth, b: Truth) -> Truth {
+        match self.0 {
+            '+' => a.and(b),
+            '|' => a.or(b),
+            '^' => a.xor(b),
+            '!' => !a,
+            _ => unreachable!(),
+        }
+    }
+}
+
+// Debug prints the bare char, so a token prints as `Operator('+')`.
+impl fmt::Debug for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// OperatorSet holds the chars a rule is written with for each operator. The default is the
+// subject's `!`, `+`, `|` and `^`, the c-style dialect writes `~` and `&` for not and and. Whatever
+// the dialect, operators are stored and displayed with the chars of the default.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OperatorSet {
+    pub not: char,
+    pub and: char,
+    pub or: char,
+    pub xor: char,
+}
+
+impl Default for OperatorSet {
+    fn default() -> Self {
+        OperatorSet {
+            not: '!',
+            and: '+',
+            or: '|',
+            xor: '^',
+        }
+    }
+}
+
+impl OperatorSet {
+    pub fn c_style() -> Self {
+        OperatorSet {
+            not: '~',
+            and: '&',
+            ..Default::default()
+        }
+    }
+
+    // Returns the operator written as the char, or None when the char isn't one in this set.
+    pub fn operator(&self, c: char) -> Option<Operator> {
+        let symbol = match c {
+            c if c == self.not => '!',
+            c if c == self.and => '+',
+            c if c == self.or => '|',
+            c if c == self.xor => '^',
+            _ => return None,
+        };
+        Some(Operator(symbol))
+    }
+
+    // Rewrites the operators of the text with the chars of the default set, e.g. `A & ~B` into
+    // `A + !B` for the c-style dialect. Other chars are kept.
+    pub fn to_default(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| self.operator(c).map_or(c, |op| op.symbol()))
+            .collect()
+    }
+}
+
+// Parses the name of a dialect: subject (the default) or c-style.
+impl FromStr for OperatorSet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "subject" => Ok(OperatorSet::default()),
+            "c-style" => Ok(OperatorSet::c_style()),
+            _ => Err(anyhow!("Invalid dialect: '{}'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_operator {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    fn truth_table(c: char) -> Result<Vec<bool>> {
+        let op = Operator::new(c)?;
+        Ok([(false, false), (false, true), (true, false), (true, true)]
+            .into_iter()
+            .map(|(a, b)| op.apply(a, b))
+            .collect())
+    }
+
+    #[test]
+    fn and() -> Result<()> {
+        assert_eq!(truth_table('+')?, vec![false, false, false, true]);
+        Ok(())
+    }
+
+    #[test]
+    fn or() -> Result<()> {
+        assert_eq!(truth_table('|')?, vec![false, true, true, true]);
+        Ok(())
+    }
+
+    #[test]
+    fn xor() -> Result<()> {
+        assert_eq!(truth_table('^')?, vec![false, true, true, false]);
+        Ok(())
+    }
+
+    #[test]
+    fn unary() -> Result<()> {
+        assert!(Operator::new('!')?.is_unary());
+        assert!(!Operator::new('+')?.is_unary());
+        assert_eq!(truth_table('!')?, vec![true, true, false, false]);
+        Ok(())
+    }
+
+    #[test]
+    fn apply3() -> Result<()> {
+        for c in ['+', '|', '^', '!'] {
+            let op = Operator::new(c)?;
+            for (a, b) in [(false, false), (false, true), (true, false), (true, true)] {
+                assert_eq!(
+                    op.apply3(Truth::from(a), Truth::from(b)),
+                    Truth::from(op.apply(a, b))
+                );
+            }
+        }
+        let apply3 =
+            |c| -> Result<Truth> { Ok(Operator::new(c)?.apply3(Truth::True, Truth::Undetermined)) };
+        assert_eq!(apply3('|')?, Truth::True);
+        assert_eq!(apply3('+')?, Truth::Undetermined);
+        assert_eq!(apply3('^')?, Truth::Undetermined);
+        assert_eq!(apply3('!')?, Truth::False);
+        Ok(())
+    }
+
+    #[test]
+    fn error_invalid() {
+        let result = Operator::new('&');
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "Invalid operator '&'");
+    }
+
+    #[test]
+    fn operator_set() -> Result<()> {
+        let c_style: OperatorSet = "c-style".parse()?;
+        assert_eq!(c_style.operator('&'), Some(Operator::new('+')?));
+        assert_eq!(c_style.operator('~'), Some(Operator::new('!')?));
+        assert_eq!(c_style.operator('|'), Some(Operator::new('|')?));
+        assert_eq!(c_style.operator('+'), None);
+        assert_eq!(c_style.operator('!'), None);
+        assert_eq!(c_style.to_default("A & ~B | C => !D"), "A + !B | C => !D");
+        assert_eq!("subject".parse::<OperatorSet>()?, OperatorSet::default());
+        assert_eq!(
+            "pascal".parse::<OperatorSet>().unwrap_err().to_string(),
+            "Invalid dialect: 'pascal'"
+        );
+        Ok(())
+    }
+}
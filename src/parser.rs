@@ -1,15 +1,20 @@
 use crate::*;
+use cancel::CancelFlag;
+use node::Node;
 use permutation_iter::PermutationIter;
 
 use anyhow::{anyhow, Context, Result};
 use std::borrow::Borrow;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::iter::Peekable;
 use std::rc::Rc;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
 use Token::*;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Direction {
     UniDirectional,
     BiDirectional,
@@ -17,18 +22,152 @@ pub enum Direction {
 
 #[derive(Debug, Copy, Clone)]
 pub enum Token {
+    Identifier(char),
     Implicator(Direction),
-    Operator(char),
+    Operator(operator::Operator),
     Parenthesis(char),
     Bool(bool),
 }
 
+// Tokens are displayed as they are written in a formula, e.g. `Identifier(A)` as `A`.
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Identifier(c) | Parenthesis(c) => write!(f, "{}", c),
+            Implicator(Direction::UniDirectional) => write!(f, "=>"),
+            Implicator(Direction::BiDirectional) => write!(f, "<=>"),
+            Operator(op) => write!(f, "{}", op),
+            Bool(b) => write!(f, "{}", *b as u8),
+        }
+    }
+}
+
+// Renders a token stream back into a formula without whitespace, e.g. `A+B=>C`.
+pub fn render_tokens(token_list: &[Token]) -> String {
+    token_list.iter().map(|t| t.to_string()).collect()
+}
+
+// Renders a token stream for debugging, e.g. `Identifier(A) Operator(+) Implicator Bool(1)`.
+pub fn dump_tokens(token_list: &[Token]) -> String {
+    token_list
+        .iter()
+        .map(|t| match t {
+            Identifier(c) => format!("Identifier({})", c),
+            Implicator(Direction::UniDirectional) => "Implicator".to_string(),
+            Implicator(Direction::BiDirectional) => "Implicator(<=>)".to_string(),
+            Operator(c) => format!("Operator({})", c),
+            Parenthesis(c) => format!("Parenthesis({})", c),
+            Bool(b) => format!("Bool({})", *b as u8),
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// ImplicatorStyle selects which spellings of the implication the tokenizer accepts: `=>`, `->` or
+// both. The bidirectional `<=>` is always accepted.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ImplicatorStyle {
+    #[default]
+    Both,
+    Fat,
+    Thin,
+}
+
+impl FromStr for ImplicatorStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "both" => Ok(ImplicatorStyle::Both),
+            "fat" => Ok(ImplicatorStyle::Fat),
+            "thin" => Ok(ImplicatorStyle::Thin),
+            _ => Err(anyhow!("Invalid implicator style: '{}'", s)),
+        }
+    }
+}
+
+// TableFormat selects how the truth tables are printed: the default text table of bits, a
+// GitHub-flavored Markdown table, see TruthTable::to_markdown, or CSV, see TruthTable::to_csv.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TableFormat {
+    #[default]
+    Text,
+    Markdown,
+    Csv(CsvOptions),
+}
+
+impl TableFormat {
+    // Renders the table in the format, fails for the Markdown and CSV formats of a streaming table,
+    // which has no rows to render.
+    pub fn render(&self, table: &TruthTable) -> Result<String> {
+        match self {
+            TableFormat::Text => Ok(table.to_string()),
+            TableFormat::Markdown => table.to_markdown(),
+            TableFormat::Csv(options) => table.to_csv(options),
+        }
+    }
+}
+
+impl FromStr for TableFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(TableFormat::Text),
+            "markdown" => Ok(TableFormat::Markdown),
+            "csv" => Ok(TableFormat::Csv(CsvOptions::default())),
+            _ => Err(anyhow!("Invalid table format: '{}'", s)),
+        }
+    }
+}
+
+// CsvOptions holds the delimiter between the cells of TruthTable::to_csv and whether a header row
+// with the variable names comes first, e.g. `;` for spreadsheets in locales using a decimal comma.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            header: true,
+        }
+    }
+}
+
 #[derive(Default)]
-pub struct RuleParser;
+pub struct RuleParser {
+    style: ImplicatorStyle,
+    operators: operator::OperatorSet,
+    internal_symbols: bool,
+}
 
 impl<'a> RuleParser {
     pub fn new() -> Self {
-        RuleParser {}
+        RuleParser::default()
+    }
+
+    pub fn with_style(style: ImplicatorStyle) -> Self {
+        RuleParser {
+            style,
+            ..Default::default()
+        }
+    }
+
+    // Tokenizes the operators as written in the given set instead of the default one.
+    pub fn with_operators(mut self, operators: operator::OperatorSet) -> Self {
+        self.operators = operators;
+        self
+    }
+
+    // Also tokenizes the internal symbols past Z, for text translated by a SymbolTable. Input
+    // written by a user only holds the identifiers A to Z.
+    pub fn with_internal_symbols(mut self) -> Self {
+        self.internal_symbols = true;
+        self
     }
 
     fn get_direction<I>(&mut self, lexer: &mut I, c: char) -> Result<Direction>
@@ -36,9 +175,12 @@ impl<'a> RuleParser {
         I: Iterator<Item = char>,
     {
         if let Some(next) = lexer.next() {
-            match (c, next) {
-                ('=', '>') => Ok(Direction::UniDirectional),
-                ('<', '=') => match lexer.next() {
+            match (c, next, self.style) {
+                ('=', '>', ImplicatorStyle::Thin) | ('-', '>', ImplicatorStyle::Fat) => Err(
+                    anyhow!("Implicator '{}>' is not allowed by the implicator style", c),
+                ),
+                ('=' | '-', '>', _) => Ok(Direction::UniDirectional),
+                ('<', '=', _) => match lexer.next() {
                     Some('>') => Ok(Direction::BiDirectional),
                     _ => Err(anyhow!("Unable to finish lexing implicator")),
                 },
@@ -53,12 +195,18 @@ impl<'a> RuleParser {
         let mut lexer = input.chars();
         let mut token_list: Vec<Token> = Vec::new();
         while let Some(c) = lexer.next() {
+            if let Some(op) = self.operators.operator(c) {
+                token_list.push(Operator(op));
+                continue;
+            }
             match c {
                 '(' | ')' => token_list.push(Parenthesis(c)),
-                '!' | '+' | '|' | '^' => token_list.push(Operator(c)),
-                '=' | '<' => token_list.push(Implicator(self.get_direction(&mut lexer, c)?)),
+                '=' | '<' | '-' => token_list.push(Implicator(self.get_direction(&mut lexer, c)?)),
                 '0' => token_list.push(Bool(false)),
                 '1' => token_list.push(Bool(true)),
+                c if is_identifier(c) || self.internal_symbols && symbol_table::is_symbol(c) => {
+                    token_list.push(Identifier(c))
+                }
                 c if c.is_whitespace() => {}
                 _ => return Err(anyhow!("Unexpected character: {}", c)),
             }
@@ -66,42 +214,45 @@ impl<'a> RuleParser {
         Ok(token_list)
     }
 
-    fn get_rule<I>(&mut self, token_list: &mut Peekable<I>) -> Result<bool>
+    fn get_rule<I>(&mut self, token_list: &mut Peekable<I>) -> Result<Node>
     where
         I: Iterator<Item = &'a Token>,
     {
         let antecedent = self.get_operator(token_list)?;
-        if let Some(implicator) = token_list.next() {
-            let consequent = self.get_operator(token_list)?;
-            match implicator {
-                Implicator(direction) => match direction {
-                    Direction::UniDirectional => Ok(!antecedent | consequent),
-                    Direction::BiDirectional => Ok(antecedent == consequent),
-                },
-                _ => unreachable!(),
+        match token_list.next() {
+            Some(Implicator(direction)) => {
+                let consequent = self.get_operator(token_list)?;
+                Ok(Node::Implication(
+                    *direction,
+                    Box::new(antecedent),
+                    Box::new(consequent),
+                ))
             }
-        } else {
-            Err(anyhow!("No implicator found"))
+            Some(t) => Err(anyhow!("Unexpected token '{:?}'", t)),
+            None => Err(anyhow!("No implicator found")),
         }
     }
 
-    fn get_operator<I>(&mut self, token_list: &mut Peekable<I>) -> Result<bool>
+    // Parses a chain of binary operators, which all have the same precedence. Every next factor is
+    // combined with the node parsed so far, so the chain groups to the left: `A+B+C` is `(A+B)+C`.
+    fn get_operator<I>(&mut self, token_list: &mut Peekable<I>) -> Result<Node>
     where
         I: Iterator<Item = &'a Token>,
     {
-        let mut node = self.get_factor(token_list);
+        let mut node = self.get_factor(token_list)?;
         while let Some(Operator(_)) = token_list.peek() {
             node = match token_list.next() {
-                Some(Operator('+')) => Ok(node? & self.get_factor(token_list)?),
-                Some(Operator('|')) => Ok(node? | self.get_factor(token_list)?),
-                Some(Operator('^')) => Ok(node? ^ self.get_factor(token_list)?),
-                _ => unreachable!(),
+                Some(Operator(op)) if !op.is_unary() => {
+                    Node::Binary(*op, Box::new(node), Box::new(self.get_factor(token_list)?))
+                }
+                Some(t) => return Err(anyhow!("Unexpected token '{:?}'", t)),
+                None => unreachable!(),
             }
         }
-        node
+        Ok(node)
     }
 
-    fn get_factor<I>(&mut self, token_list: &mut Peekable<I>) -> Result<bool>
+    fn get_factor<I>(&mut self, token_list: &mut Peekable<I>) -> Result<Node>
     where
         I: Iterator<Item = &'a Token>,
     {
@@ -113,19 +264,66 @@ impl<'a> RuleParser {
                     _ => Err(anyhow!("Missing closing parenthesis")),
                 }
             }
-            Some(Operator('!')) => Ok(!self.get_factor(token_list)?),
-            Some(Bool(b)) => Ok(*b),
+            Some(Operator(op)) if op.is_unary() => {
+                Ok(Node::Not(Box::new(self.get_factor(token_list)?)))
+            }
+            Some(Identifier(c)) => Ok(Node::Identifier(*c)),
+            Some(Bool(b)) => Ok(Node::Bool(*b)),
             Some(t) => Err(anyhow!("Invalid factor token '{:?}'", t)),
             None => Err(anyhow!("Unexpected end of token list")),
         }
     }
 
-    pub fn evaluate(&mut self, input: &str) -> Result<bool> {
+    // Parses a rule into its syntax tree, the root node is always an implication.
+    pub fn parse(&mut self, input: &str) -> Result<Node> {
         let token_list = self
             .tokenize(input)
             .context(format!("Failed to tokenize input: '{}'", input))?;
         self.get_rule(&mut token_list.iter().peekable())
     }
+
+    // Parses a rule like parse, or an expression without implicator, e.g. `A + B`, as the rule
+    // `1 => A + B`, which holds exactly when the expression does.
+    pub fn parse_formula(&mut self, input: &str) -> Result<Node> {
+        let token_list = self
+            .tokenize(input)
+            .context(format!("Failed to tokenize input: '{}'", input))?;
+        if token_list.iter().any(|t| matches!(t, Implicator(_))) {
+            return self.get_rule(&mut token_list.iter().peekable());
+        }
+        let rule = [Bool(true), Implicator(Direction::UniDirectional)];
+        self.get_rule(&mut rule.iter().chain(token_list.iter()).peekable())
+    }
+
+    // Evaluates a permutation, i.e. a rule in which every identifier has been substituted.
+    pub fn evaluate(&mut self, input: &str) -> Result<bool> {
+        let token_list = self
+            .tokenize(input)
+            .and_then(
+                |token_list| match token_list.iter().find(|t| matches!(t, Identifier(_))) {
+                    Some(t) => Err(anyhow!("Unexpected identifier '{:?}'", t)),
+                    None => Ok(token_list),
+                },
+            )
+            .context(format!("Failed to tokenize input: '{}'", input))?;
+        self.get_rule(&mut token_list.iter().peekable())?
+            .eval(&state::State::default())
+    }
+}
+
+// Builds the syntax tree of a rule from its tokens without tokenizing, e.g. from a hand-built token
+// list. The implicator style doesn't apply since it is only checked while tokenizing.
+impl FromIterator<Token> for Result<Node> {
+    fn from_iter<I: IntoIterator<Item = Token>>(tokens: I) -> Self {
+        let token_list: Vec<Token> = tokens.into_iter().collect();
+        RuleParser::new().get_rule(&mut token_list.iter().peekable())
+    }
+}
+
+impl Node {
+    pub fn from_tokens<I: IntoIterator<Item = Token>>(tokens: I) -> Result<Node> {
+        tokens.into_iter().collect()
+    }
 }
 
 // TruthTable struct holds the truth table data of an input rule.
@@ -140,6 +338,28 @@ impl<'a> RuleParser {
 pub struct TruthTable {
     pub variables: Vec<char>,
     pub results: Vec<bool>,
+    // Only set for a table built in TableMode::Streaming, which has no results.
+    summary: Option<RowSummary>,
+}
+
+// Row holds the assignment of the variables of a truth table and the result for it.
+type Row = (Vec<bool>, bool);
+
+// TableMode selects whether building a truth table stores every row (Full) or only the number of
+// true rows and the first of them (Streaming), for when the rows themselves aren't needed.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TableMode {
+    #[default]
+    Full,
+    Streaming,
+}
+
+// RowSummary holds what a streaming truth table keeps of its rows: how many are true and the index
+// of the first true row.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+struct RowSummary {
+    true_rows: usize,
+    witness: Option<usize>,
 }
 
 impl TruthTable {
@@ -147,8 +367,262 @@ impl TruthTable {
         TruthTable {
             variables: Vec::new(),
             results: Vec::new(),
+            summary: None,
+        }
+    }
+
+    // Builds the truth table of an expression without implication, e.g. `A + B`, over its own
+    // variables. It is evaluated as the rule `1 => A + B`, which holds exactly when it does.
+    pub fn from_expression(expression: &str) -> Result<Self> {
+        PermutationIter::try_new(format!("1 => {}", expression))
+            .and_then(TruthTable::try_from)
+            .context(format!(
+                "Failed to create truth table from: '{}'",
+                expression
+            ))
+    }
+
+    // Builds the truth table of the permutations in the given mode, TryFrom builds a full table.
+    pub fn try_from_mode(mut permutation_iter: PermutationIter, mode: TableMode) -> Result<Self> {
+        if mode == TableMode::Full {
+            return Self::try_from(permutation_iter);
+        }
+        let mut summary = RowSummary::default();
+        let mut parser = RuleParser::new();
+        for (index, permutation) in permutation_iter.by_ref().enumerate() {
+            if evaluate_row(&mut parser, &permutation)? {
+                summary.true_rows += 1;
+                summary.witness.get_or_insert(index);
+            }
+        }
+        Ok(TruthTable {
+            variables: std::mem::take(&mut permutation_iter.variables),
+            results: Vec::new(),
+            summary: Some(summary),
+        })
+    }
+
+    pub fn mode(&self) -> TableMode {
+        match self.summary {
+            Some(_) => TableMode::Streaming,
+            None => TableMode::Full,
+        }
+    }
+
+    // Returns the number of rows, one for every assignment of the variables.
+    pub fn rows(&self) -> usize {
+        1 << self.variables.len()
+    }
+
+    // Returns the number of rows of which the result is true.
+    pub fn true_rows(&self) -> usize {
+        match self.summary {
+            Some(summary) => summary.true_rows,
+            None => self.results.iter().filter(|r| **r).count(),
+        }
+    }
+
+    // Returns the assignment of the first row of which the result is true, in the order of the
+    // variables, or None when no row is.
+    pub fn witness(&self) -> Option<Vec<bool>> {
+        let index = match self.summary {
+            Some(summary) => summary.witness,
+            None => self.results.iter().position(|r| *r),
+        }?;
+        Some(self.assignment(index))
+    }
+
+    pub fn is_satisfiable(&self) -> bool {
+        self.true_rows() > 0
+    }
+
+    // Fails for a streaming table, of which the rows are not stored.
+    fn check_rows(&self) -> Result<()> {
+        match self.summary {
+            Some(_) => Err(anyhow!("Rows of a streaming truth table are not stored")),
+            None => Ok(()),
+        }
+    }
+
+    // Returns the assignment of the variables and the result of the row at the index, in the
+    // order of the variables. Fails when the table is streaming or the index is out of range.
+    pub fn try_row(&self, index: usize) -> Result<(Vec<bool>, bool)> {
+        self.check_rows()?;
+        let result = self
+            .results
+            .get(index)
+            .context(format!("Row out of range: {}", index))?;
+        Ok((self.assignment(index), *result))
+    }
+
+    fn assignment(&self, index: usize) -> Vec<bool> {
+        let len = self.variables.len();
+        (0..len)
+            .map(|i| index & (1 << (len - 1 - i)) != 0)
+            .collect()
+    }
+
+    // Returns the variables the table covers, the first variable is the most significant bit of
+    // the row index. Tables built from a PermutationIter have their variables sorted.
+    pub fn variables(&self) -> &[char] {
+        &self.variables
+    }
+
+    // Returns the table extended by a variable that doesn't affect the results. The new variable
+    // becomes the last (least significant) one, so every row is duplicated: once for the new
+    // variable being 0 and once for it being 1. A variable that is already present is ignored.
+    pub fn extend_with_variable(&self, new_var: char) -> TruthTable {
+        if self.variables.contains(&new_var) {
+            return self.clone();
+        }
+        let mut variables = self.variables.clone();
+        variables.push(new_var);
+        TruthTable {
+            variables,
+            results: self.results.iter().flat_map(|r| [*r, *r]).collect(),
+            summary: self.summary.map(|summary| RowSummary {
+                true_rows: summary.true_rows * 2,
+                witness: summary.witness.map(|index| index * 2),
+            }),
         }
     }
+
+    // Returns the variables in sorted order with the rows ordered by them, the first sorted
+    // variable is the most significant bit. Each row holds the assignment in the sorted order and
+    // the result. Fails for a streaming table.
+    fn sorted_rows(&self) -> Result<(Vec<char>, Vec<Row>)> {
+        self.check_rows()?;
+        let len = self.variables.len();
+        let mut sorted = self.variables.clone();
+        sorted.sort_unstable();
+        // The bit of each sorted variable in the row index of the table.
+        let bits: Vec<usize> = sorted
+            .iter()
+            .map(|v| len - 1 - self.variables.iter().position(|c| c == v).unwrap())
+            .collect();
+        let rows = (0..self.results.len())
+            .map(|row| {
+                let mut index = 0;
+                let mut assignment = vec![];
+                for (b, bit) in bits.iter().enumerate() {
+                    let value = row & (1 << (len - 1 - b)) != 0;
+                    assignment.push(value);
+                    index |= (value as usize) << bit;
+                }
+                (assignment, self.results[index])
+            })
+            .collect();
+        Ok((sorted, rows))
+    }
+
+    // Returns the rows where the tables disagree, each with the assignment of the variables in
+    // sorted order followed by the result of this table and that of the other. Both tables have
+    // to cover the same variables, in any order, and neither can be streaming.
+    pub fn difference(&self, other: &TruthTable) -> Result<Vec<(Vec<bool>, bool, bool)>> {
+        let (variables, rows) = self.sorted_rows()?;
+        let (other_variables, other_rows) = other.sorted_rows()?;
+        if variables != other_variables {
+            return Err(anyhow!(
+                "Truth tables cover different variables: '{}' and '{}'",
+                variables.iter().collect::<String>(),
+                other_variables.iter().collect::<String>()
+            ));
+        }
+        Ok(rows
+            .into_iter()
+            .zip(other_rows)
+            .filter(|((_, result), (_, other_result))| result != other_result)
+            .map(|((assignment, result), (_, other_result))| (assignment, result, other_result))
+            .collect())
+    }
+
+    // Returns the table as CSV with `0`/`1` cells, with the rows ordered like Display. The header
+    // row holds the variables and `Result`. Example for `A => B` with the default options:
+    // A,B,Result
+    // 0,0,1
+    // 0,1,1
+    // 1,0,0
+    // 1,1,1
+    // Fails for a streaming table.
+    pub fn to_csv(&self, options: &CsvOptions) -> Result<String> {
+        let delimiter = options.delimiter.to_string();
+        let (sorted, rows) = self.sorted_rows()?;
+        let mut result = String::new();
+        if options.header {
+            let mut header: Vec<String> = sorted.iter().map(|v| v.to_string()).collect();
+            header.push("Result".to_string());
+            result.push_str(&format!("{}\n", header.join(&delimiter)));
+        }
+        for (assignment, value) in rows {
+            let cells: Vec<String> = assignment
+                .iter()
+                .chain([&value])
+                .map(|b| (*b as u8).to_string())
+                .collect();
+            result.push_str(&format!("{}\n", cells.join(&delimiter)));
+        }
+        Ok(result)
+    }
+
+    // Returns the table as a GitHub-flavored Markdown table, with the rows ordered like Display
+    // and `T`/`F` cells. Example for `A => B`:
+    // | A | B | Result |
+    // |---|---|---|
+    // | F | F | T |
+    // | F | T | T |
+    // | T | F | F |
+    // | T | T | T |
+    // Fails for a streaming table.
+    pub fn to_markdown(&self) -> Result<String> {
+        let cell = |value: bool| if value { "T" } else { "F" };
+        let (sorted, rows) = self.sorted_rows()?;
+        let mut result = String::new();
+        for v in &sorted {
+            result.push_str(&format!("| {} ", v));
+        }
+        result.push_str("| Result |\n");
+        result.push_str(&format!("{}|\n", "|---".repeat(sorted.len() + 1)));
+        for (assignment, value) in rows {
+            for a in assignment {
+                result.push_str(&format!("| {} ", cell(a)));
+            }
+            result.push_str(&format!("| {} |\n", cell(value)));
+        }
+        Ok(result)
+    }
+}
+
+// Builds the truth table of a formula with rayon, evaluating the rows in parallel. Each row is
+// evaluated from its index, so the rows are collected in order without sorting them afterwards.
+#[cfg(feature = "rayon")]
+impl TruthTable {
+    pub fn build_parallel(formula: &str) -> Result<Self> {
+        use rayon::prelude::*;
+
+        let permutation_iter = PermutationIter::try_new(formula)?;
+        let results = (0..permutation_iter.len())
+            .into_par_iter()
+            .map_init(RuleParser::new, |parser, index| {
+                evaluate_row(parser, &permutation_iter.permutation(index))
+            })
+            .collect::<Result<Vec<bool>>>()?;
+        Ok(TruthTable {
+            variables: permutation_iter.variables,
+            results,
+            summary: None,
+        })
+    }
+}
+
+// Evaluates a row of a truth table. The blank permutation of an empty formula is its single,
+// trivial row, which holds.
+fn evaluate_row(parser: &mut RuleParser, permutation: &str) -> Result<bool> {
+    if permutation.trim().is_empty() {
+        return Ok(true);
+    }
+    parser
+        .evaluate(permutation)
+        .context(format!("Failed to evaluate permutation {}", permutation))
 }
 
 impl TryFrom<PermutationIter> for TruthTable {
@@ -158,43 +632,57 @@ impl TryFrom<PermutationIter> for TruthTable {
         let mut table = Self::new();
         let mut parser = RuleParser::new();
         for permutation in permutation_iter.by_ref() {
-            table.results.push(
-                parser
-                    .evaluate(&permutation)
-                    .context(format!("Failed to evaluate permutation {}", permutation))?,
-            );
+            table.results.push(evaluate_row(&mut parser, &permutation)?);
         }
         table.variables.append(&mut permutation_iter.variables);
         Ok(table)
     }
 }
 
-impl fmt::Debug for TruthTable {
+// Displays the table with one column of width 3 per variable in sorted order, followed by the `=`
+// column of the results. Rows are ordered by the sorted variables, the first is the most
+// significant bit. Example for `A => B`:
+// | A | B | = |
+// |---|---|---|
+// | 0 | 0 | 1 |
+// | 0 | 1 | 1 |
+// | 1 | 0 | 0 |
+// | 1 | 1 | 1 |
+// A streaming table has no rows, it displays the number of true rows after the header instead.
+impl fmt::Display for TruthTable {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let len = self.variables.len();
-        for v in &self.variables {
+        let mut sorted = self.variables.clone();
+        sorted.sort_unstable();
+        for v in &sorted {
             write!(f, "| {} ", v)?;
         }
         writeln!(f, "| = |")?;
-        writeln!(f, "{}|", "|---".repeat(len + 1))?;
-        for (i, result) in self.results.iter().enumerate() {
-            for b in 0..len {
-                write!(
-                    f,
-                    "| {} ",
-                    if i & (1 << (len - 1 - b)) == 0 { 0 } else { 1 }
-                )?
+        writeln!(f, "{}|", "|---".repeat(sorted.len() + 1))?;
+        let Ok((_, rows)) = self.sorted_rows() else {
+            return writeln!(f, "{} of {} rows are true", self.true_rows(), self.rows());
+        };
+        for (assignment, value) in rows {
+            for a in assignment {
+                write!(f, "| {} ", a as u8)?;
             }
-            writeln!(f, "| {} |", if *result { 1 } else { 0 })?;
+            writeln!(f, "| {} |", value as u8)?;
         }
         Ok(())
     }
 }
 
-// Structure that holds key pairs of identifier and all related truth tables.
+impl fmt::Debug for TruthTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+// Structure that holds key pairs of identifier and all related truth tables. The identifiers are
+// kept in sorted order and their tables in insertion order, so rendering the map is stable.
 #[derive(Default)]
 pub struct RuleMap {
-    map: HashMap<char, HashSet<Rc<TruthTable>>>,
+    map: BTreeMap<char, Vec<Rc<TruthTable>>>,
+    timings: Vec<(String, Duration)>,
 }
 
 impl RuleMap {
@@ -203,20 +691,25 @@ impl RuleMap {
     where
         T: Borrow<str>,
     {
-        let ptr = Rc::new(
-            TruthTable::try_from(PermutationIter::new(rule.borrow())).context(format!(
-                "Failed to create truth table from: '{}'",
-                rule.borrow()
-            ))?,
-        );
+        let (table, duration) = build_table(rule.borrow())?;
+        self.add_table(rule.borrow(), table, duration);
+        Ok(())
+    }
+
+    fn add_table(&mut self, rule: &str, table: TruthTable, duration: Duration) {
+        let ptr = Rc::new(table);
+        self.timings.push((rule.to_string(), duration));
         for v in ptr.variables.iter() {
-            let tables = self
-                .map
-                .entry(*v)
-                .or_insert_with(|| HashSet::from([Rc::clone(&ptr)]));
-            tables.insert(Rc::clone(&ptr));
+            let tables = self.map.entry(*v).or_default();
+            if !tables.contains(&ptr) {
+                tables.push(Rc::clone(&ptr));
+            }
         }
-        Ok(())
+    }
+
+    // Returns how long building the truth table of each inserted rule took, in insertion order.
+    pub fn timings(&self) -> &[(String, Duration)] {
+        &self.timings
     }
 
     pub fn insert_vec<T>(&mut self, rules: Vec<T>) -> Result<()>
@@ -228,6 +721,130 @@ impl RuleMap {
         }
         Ok(())
     }
+
+    // Builds the truth tables of the rules until the flag is cancelled, the rules that were not
+    // reached yet are left out.
+    pub fn try_from_cancellable<T>(rules: Vec<T>, cancel: &CancelFlag) -> Result<Self>
+    where
+        T: Borrow<str>,
+    {
+        let mut map = RuleMap::default();
+        for rule in rules.iter() {
+            if cancel.is_cancelled() {
+                break;
+            }
+            map.insert(rule.borrow())?
+        }
+        Ok(map)
+    }
+
+    // Builds the truth tables of the rules on up to `threads` threads, each building the tables of
+    // a consecutive chunk of rules. The tables are inserted in rule order, so the result is the
+    // same as building them one by one. A single thread builds them on the current thread.
+    // A thread that panics fails the whole build instead of leaving out the tables of its chunk.
+    // There are no threads to build on in the browser, so the wasm build leaves this out.
+    #[cfg(not(feature = "wasm"))]
+    pub fn try_from_parallel<T>(rules: Vec<T>, threads: usize, cancel: &CancelFlag) -> Result<Self>
+    where
+        T: Borrow<str>,
+    {
+        if threads <= 1 {
+            return Self::try_from_cancellable(rules, cancel);
+        }
+        Self::build_parallel_with(rules, threads, cancel, build_table)
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    fn build_parallel_with<T>(
+        rules: Vec<T>,
+        threads: usize,
+        cancel: &CancelFlag,
+        build: fn(&str) -> Result<(TruthTable, Duration)>,
+    ) -> Result<Self>
+    where
+        T: Borrow<str>,
+    {
+        let rules: Vec<&str> = rules.iter().map(|r| r.borrow()).collect();
+        let chunk_size = rules.len().div_ceil(threads).max(1);
+        let built: Vec<Option<Result<(TruthTable, Duration)>>> = thread::scope(|s| {
+            let handles: Vec<_> = rules
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    s.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|r| (!cancel.is_cancelled()).then(|| build(r)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            // Every thread is joined before reporting a panic, the scope would panic on a thread
+            // that is left unjoined.
+            let joined: Vec<_> = handles.into_iter().map(|h| h.join()).collect();
+            let mut built = vec![];
+            for tables in joined {
+                built.extend(tables.map_err(|payload| {
+                    let message = payload
+                        .downcast_ref::<&str>()
+                        .map(|m| m.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    anyhow!("Truth table thread panicked: '{}'", message)
+                })?);
+            }
+            Ok::<_, anyhow::Error>(built)
+        })?;
+        let mut map = RuleMap::default();
+        for (rule, table) in rules.iter().zip(built) {
+            let Some(table) = table else {
+                break;
+            };
+            let (table, duration) = table?;
+            map.add_table(rule, table, duration);
+        }
+        Ok(map)
+    }
+}
+
+// Builds the truth table of a rule, returns it along with how long building it took. With the
+// rayon feature the rows of the table are evaluated in parallel as well.
+fn build_table(rule: &str) -> Result<(TruthTable, Duration)> {
+    let start = Instant::now();
+    #[cfg(feature = "rayon")]
+    let table = TruthTable::build_parallel(rule);
+    #[cfg(not(feature = "rayon"))]
+    let table = PermutationIter::try_new(rule).and_then(TruthTable::try_from);
+    let table = table.context(format!("Failed to create truth table from: '{}'", rule))?;
+    Ok((table, start.elapsed()))
+}
+
+// Returns the number of threads to build truth tables with: the available parallelism, capped at
+// `max_threads` when given.
+pub fn thread_count(max_threads: Option<usize>) -> usize {
+    let available = thread::available_parallelism().map_or(1, |n| n.get());
+    max_threads.map_or(available, |max| available.min(max))
+}
+
+// Number of truth table rows above which building the tables asks for confirmation first.
+pub const ROW_LIMIT: u64 = 1 << 24;
+
+// Returns the number of rows of the largest truth table of the rules when it exceeds the limit and
+// the tables are not built without asking, e.g. by --yes.
+pub fn rows_to_confirm<T>(rules: &[T], limit: u64, yes: bool) -> Result<Option<u64>>
+where
+    T: Borrow<str>,
+{
+    if yes {
+        return Ok(None);
+    }
+    let mut max = None;
+    for rule in rules.iter() {
+        let variables: BTreeSet<char> =
+            rule.borrow().chars().filter(|c| is_identifier(c)).collect();
+        let rows = permutation_iter::permutation_count(variables.len())? as u64;
+        max = max.max(Some(rows));
+    }
+    Ok(max.filter(|rows| *rows > limit))
 }
 
 impl<T> TryFrom<Vec<T>> for RuleMap
@@ -243,21 +860,30 @@ where
     }
 }
 
-impl fmt::Debug for RuleMap {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl RuleMap {
+    // Renders every symbol followed by its truth tables in the given format, as printed by Debug
+    // in the text format.
+    pub fn render(&self, format: TableFormat) -> String {
+        let mut result = String::new();
         let mut map = self.map.iter().peekable();
         while let Some((k, v)) = map.next() {
-            writeln!(f, "{}", k)?;
+            result.push_str(&format!("{}\n", k));
             let mut table = v.iter().peekable();
             while let Some(t) = table.next() {
-                if map.peek().is_none() && table.peek().is_none() {
-                    write!(f, "{:?}", t)?;
-                } else {
-                    writeln!(f, "{:?}", t)?;
+                // The tables of a rule map are built in full.
+                result.push_str(&format.render(t).expect("Full truth tables always render"));
+                if map.peek().is_some() || table.peek().is_some() {
+                    result.push('\n');
                 }
             }
         }
-        Ok(())
+        result
+    }
+}
+
+impl fmt::Debug for RuleMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(TableFormat::Text))
     }
 }
 
@@ -278,6 +904,131 @@ mod tests_rule_parser {
         Ok(())
     }
 
+    #[test]
+    fn thin_arrow() -> Result<()> {
+        let mut parser = RuleParser::new();
+        assert_eq!(parser.parse("A->B")?, parser.parse("A=>B")?);
+        assert_eq!(parser.evaluate("1 -> 0")?, false);
+        assert_eq!(
+            TruthTable::try_from(PermutationIter::try_new("A + B -> C")?)?,
+            TruthTable::try_from(PermutationIter::try_new("A + B => C")?)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn implicator_style() -> Result<()> {
+        let mut parser = RuleParser::with_style("thin".parse()?);
+        assert_eq!(parser.parse("A->B")?, RuleParser::new().parse("A=>B")?);
+        let result = parser.parse("A=>B");
+        assert!(result.is_err());
+        assert_eq!(
+            format!("{:#}", result.unwrap_err()),
+            "Failed to tokenize input: 'A=>B': Implicator '=>' is not allowed by the implicator style"
+        );
+        let mut parser = RuleParser::with_style(ImplicatorStyle::Fat);
+        assert!(parser.parse("A->B").is_err());
+        assert!(parser.parse("A<=>B").is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn c_style_operators() -> Result<()> {
+        let mut parser = RuleParser::new().with_operators("c-style".parse()?);
+        let mut default = RuleParser::new();
+        assert_eq!(
+            dump_tokens(&parser.tokenize("A & B")?),
+            dump_tokens(&default.tokenize("A+B")?)
+        );
+        assert_eq!(parser.parse("A & B => C")?, default.parse("A+B => C")?);
+        assert_eq!(
+            parser.parse("~(A | B) ^ C => ~D")?,
+            default.parse("!(A | B) ^ C => !D")?
+        );
+        for formula in ["A + B => C", "!A => B"] {
+            let result = parser.parse(formula);
+            assert!(result.is_err());
+        }
+        assert_eq!(
+            parser.tokenize("A + B").unwrap_err().to_string(),
+            "Unexpected character: +"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_invalid_implicator_style() {
+        let result = "arrow".parse::<ImplicatorStyle>();
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid implicator style: 'arrow'"
+        );
+    }
+
+    #[test]
+    fn from_tokens() -> Result<()> {
+        let and = operator::Operator::new('+')?;
+        let node = Node::from_tokens(vec![
+            Identifier('A'),
+            Operator(and),
+            Parenthesis('('),
+            Operator(operator::Operator::new('!')?),
+            Identifier('B'),
+            Parenthesis(')'),
+            Implicator(Direction::UniDirectional),
+            Identifier('C'),
+        ])?;
+        assert_eq!(
+            node,
+            Node::Implication(
+                Direction::UniDirectional,
+                Box::new(Node::Binary(
+                    and,
+                    Box::new(Node::Identifier('A')),
+                    Box::new(Node::Not(Box::new(Node::Identifier('B'))))
+                )),
+                Box::new(Node::Identifier('C'))
+            )
+        );
+        assert_eq!(node, RuleParser::new().parse("A+(!B)=>C")?);
+
+        let tokens = RuleParser::new().tokenize("A<=>B")?;
+        let result: Result<Node> = tokens.into_iter().collect();
+        assert_eq!(result?, RuleParser::new().parse("A<=>B")?);
+
+        let result = Node::from_tokens(vec![Identifier('A'), Identifier('B')]);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unexpected token 'Identifier('B')'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn left_associative() -> Result<()> {
+        let mut parser = RuleParser::new();
+        for op in ['+', '|', '^'] {
+            let chain = parser.parse(&format!("A{0}B{0}C=>Z", op))?;
+            assert_eq!(chain, parser.parse(&format!("(A{0}B){0}C=>Z", op))?);
+            assert_ne!(chain, parser.parse(&format!("A{0}(B{0}C)=>Z", op))?);
+            let Node::Implication(_, premise, _) = chain else {
+                unreachable!()
+            };
+            let Node::Binary(_, left, right) = *premise else {
+                panic!("Expected a binary premise")
+            };
+            assert!(matches!(*left, Node::Binary(..)));
+            assert_eq!(*right, Node::Identifier('C'));
+        }
+        // The operators are associative, so both groupings have the same truth table.
+        assert_eq!(
+            TruthTable::try_from(PermutationIter::try_new("A+B+C=>Z")?)?,
+            TruthTable::try_from(PermutationIter::try_new("A+(B+C)=>Z")?)?
+        );
+        Ok(())
+    }
+
     #[test]
     fn bi_directional() -> Result<()> {
         let mut parser = RuleParser::new();
@@ -421,6 +1172,64 @@ mod tests_rule_parser {
     }
 }
 
+#[cfg(test)]
+mod tests_dump_tokens {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn simple() -> Result<()> {
+        let tokens = RuleParser::new().tokenize("A+B=>C")?;
+        assert_eq!(
+            dump_tokens(&tokens),
+            "Identifier(A) Operator(+) Identifier(B) Implicator Identifier(C)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn all() -> Result<()> {
+        let tokens = RuleParser::new().tokenize("!(A^0)<=>1")?;
+        assert_eq!(
+            dump_tokens(&tokens),
+            "Operator(!) Parenthesis(() Identifier(A) Operator(^) Bool(0) Parenthesis()) Implicator(<=>) Bool(1)"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(dump_tokens(&[]), "");
+    }
+}
+
+#[cfg(test)]
+mod tests_render_tokens {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        for formula in ["A+B=>C", "!(A^0)<=>1", "(A|B)+!C=>D"] {
+            let tokens = RuleParser::new().tokenize(formula)?;
+            assert_eq!(render_tokens(&tokens), formula);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn thin_implicator() -> Result<()> {
+        let tokens = RuleParser::new().tokenize("A->B")?;
+        assert_eq!(render_tokens(&tokens), "A=>B");
+        assert_eq!(tokens[1].to_string(), "=>");
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests_truth_table {
     use super::*;
@@ -430,15 +1239,322 @@ mod tests_truth_table {
 
     #[test]
     fn simple() -> Result<()> {
-        let result = TruthTable::try_from(PermutationIter::new("A => Z"))?;
+        let result = TruthTable::try_from(PermutationIter::try_new("A => Z")?)?;
         assert_eq!(result.variables, vec!['A', 'Z']);
         assert_eq!(result.results, vec![true, true, false, true]);
         Ok(())
     }
 
+    #[test]
+    fn display() -> Result<()> {
+        let result = TruthTable::try_from(PermutationIter::try_new("A => B")?)?;
+        assert_eq!(
+            result.to_string(),
+            indoc! {"
+                | A | B | = |
+                |---|---|---|
+                | 0 | 0 | 1 |
+                | 0 | 1 | 1 |
+                | 1 | 0 | 0 |
+                | 1 | 1 | 1 |
+            "}
+        );
+        assert_eq!(format!("{:?}", result), result.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn constant() -> Result<()> {
+        for (formula, result) in [("1 => 0", false), ("0 => 0", true), ("1 <=> 1", true)] {
+            let table = TruthTable::try_from(PermutationIter::try_new(formula)?)?;
+            assert_eq!(table.variables, Vec::<char>::new());
+            assert_eq!(table.results, vec![result]);
+            assert_eq!(table.try_row(0)?, (vec![], result));
+            let streaming = TruthTable::try_from_mode(
+                PermutationIter::try_new(formula)?,
+                TableMode::Streaming,
+            )?;
+            assert_eq!(streaming.rows(), 1);
+            assert_eq!(streaming.is_satisfiable(), result);
+        }
+        assert_eq!(
+            TruthTable::try_from(PermutationIter::try_new("1 => 0")?)?.to_string(),
+            "| = |\n|---|\n| 0 |\n"
+        );
+        assert_eq!(TruthTable::from_expression("1")?.results, vec![true]);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_formula() -> Result<()> {
+        for formula in ["", " "] {
+            let result = TruthTable::try_from(PermutationIter::try_new(formula)?)?;
+            assert_eq!(result.variables, vec![]);
+            assert_eq!(result.results, vec![true]);
+            assert_eq!(result.rows(), 1);
+            let streaming = TruthTable::try_from_mode(
+                PermutationIter::try_new(formula)?,
+                TableMode::Streaming,
+            )?;
+            assert_eq!(streaming.true_rows(), 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn from_expression() -> Result<()> {
+        let result = TruthTable::from_expression("A + B")?;
+        assert_eq!(result.variables, vec!['A', 'B']);
+        assert_eq!(result.results, vec![false, false, false, true]);
+        assert_eq!(
+            TruthTable::from_expression("A +").unwrap_err().to_string(),
+            "Failed to create truth table from: 'A +'"
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn build_parallel() -> Result<()> {
+        for formula in ["A => Z", "A + B <=> C", "A | !(B ^ C) => D + E", "1 => 0"] {
+            let sequential = TruthTable::try_from(PermutationIter::try_new(formula)?)?;
+            assert_eq!(TruthTable::build_parallel(formula)?, sequential);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn difference() -> Result<()> {
+        let lhs = TruthTable::try_from(PermutationIter::try_new("A => B")?)?;
+        let rhs = TruthTable::try_from(PermutationIter::try_new("B => A")?)?;
+        assert_eq!(
+            lhs.difference(&rhs)?,
+            vec![
+                (vec![false, true], true, false),
+                (vec![true, false], false, true)
+            ]
+        );
+        assert_eq!(lhs.difference(&lhs)?, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn error_difference() -> Result<()> {
+        let lhs = TruthTable::try_from(PermutationIter::try_new("A => B")?)?;
+        let rhs = TruthTable::try_from(PermutationIter::try_new("A => C")?)?;
+        let result = lhs.difference(&rhs);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Truth tables cover different variables: 'AB' and 'AC'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn to_csv() -> Result<()> {
+        let result = TruthTable::try_from(PermutationIter::try_new("A => B")?)?;
+        assert_eq!(
+            result.to_csv(&CsvOptions::default())?,
+            indoc! {"
+                A,B,Result
+                0,0,1
+                0,1,1
+                1,0,0
+                1,1,1
+            "}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn to_csv_semicolon() -> Result<()> {
+        let result = TruthTable::try_from(PermutationIter::try_new("A => B")?)?;
+        let options = CsvOptions {
+            delimiter: ';',
+            ..Default::default()
+        };
+        assert_eq!(
+            result.to_csv(&options)?,
+            indoc! {"
+                A;B;Result
+                0;0;1
+                0;1;1
+                1;0;0
+                1;1;1
+            "}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn to_csv_no_header() -> Result<()> {
+        let result = TruthTable::try_from(PermutationIter::try_new("A => B")?)?;
+        let options = CsvOptions {
+            header: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            result.to_csv(&options)?,
+            indoc! {"
+                0,0,1
+                0,1,1
+                1,0,0
+                1,1,1
+            "}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn to_markdown() -> Result<()> {
+        let result = TruthTable::try_from(PermutationIter::try_new("A => B")?)?;
+        assert_eq!(
+            result.to_markdown()?,
+            indoc! {"
+                | A | B | Result |
+                |---|---|---|
+                | F | F | T |
+                | F | T | T |
+                | T | F | F |
+                | T | T | T |
+            "}
+        );
+        assert_eq!(
+            TableFormat::Markdown.render(&result)?,
+            result.to_markdown()?
+        );
+        assert_eq!(TableFormat::Text.render(&result)?, result.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn error_invalid_table_format() {
+        let result = "html".parse::<TableFormat>();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid table format: 'html'"
+        );
+    }
+
+    #[test]
+    fn display_sorted() -> Result<()> {
+        let table = TruthTable::try_from(PermutationIter::try_new("B => C")?)?;
+        let extended = table.extend_with_variable('A');
+        assert_eq!(extended.variables(), &['B', 'C', 'A']);
+        assert_eq!(
+            extended.to_string(),
+            TruthTable::try_from(PermutationIter::try_new("B => C | (A + 0)")?)?.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn row() -> Result<()> {
+        let result = TruthTable::try_from(PermutationIter::try_new("A + B => 0")?)?;
+        assert_eq!(result.try_row(3)?, (vec![true, true], false));
+        assert_eq!(result.try_row(1)?, (vec![false, true], true));
+        let result = TruthTable::try_from(PermutationIter::try_new("1 => A + B")?)?;
+        assert_eq!(result.try_row(3)?, (vec![true, true], true));
+        Ok(())
+    }
+
+    #[test]
+    fn streaming() -> Result<()> {
+        let table = TruthTable::try_from_mode(
+            PermutationIter::try_new("A + B => C")?,
+            TableMode::Streaming,
+        )?;
+        let full = TruthTable::try_from(PermutationIter::try_new("A + B => C")?)?;
+        assert_eq!(table.mode(), TableMode::Streaming);
+        assert_eq!(table.results.capacity(), 0);
+        assert_eq!(table.rows(), 8);
+        assert_eq!(table.true_rows(), 7);
+        assert_eq!(table.true_rows(), full.true_rows());
+        assert_eq!(table.witness(), Some(vec![false, false, false]));
+        assert_eq!(table.witness(), full.witness());
+        assert!(table.is_satisfiable());
+        assert_eq!(
+            table.try_row(0).unwrap_err().to_string(),
+            "Rows of a streaming truth table are not stored"
+        );
+
+        for result in [
+            table.to_markdown(),
+            table.to_csv(&CsvOptions::default()),
+            TableFormat::Markdown.render(&table),
+            table.difference(&full).map(|_| String::new()),
+            full.difference(&table).map(|_| String::new()),
+        ] {
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "Rows of a streaming truth table are not stored"
+            );
+        }
+        assert_eq!(
+            table.to_string(),
+            indoc! {"
+                | A | B | C | = |
+                |---|---|---|---|
+                7 of 8 rows are true
+            "}
+        );
+
+        let table = table.extend_with_variable('D');
+        assert_eq!((table.rows(), table.true_rows()), (16, 14));
+        assert_eq!(table.witness(), Some(vec![false; 4]));
+        assert_eq!(table.witness(), full.extend_with_variable('D').witness());
+
+        let table = TruthTable::try_from_mode(
+            PermutationIter::try_new("1 => A + !A")?,
+            TableMode::Streaming,
+        )?;
+        assert_eq!(table.true_rows(), 0);
+        assert_eq!(table.witness(), None);
+        assert!(!table.is_satisfiable());
+        Ok(())
+    }
+
+    #[test]
+    fn try_row() -> Result<()> {
+        let table =
+            TruthTable::try_from_mode(PermutationIter::try_new("A => B")?, TableMode::Full)?;
+        assert_eq!(table.mode(), TableMode::Full);
+        assert_eq!(table.try_row(2)?, (vec![true, false], false));
+        assert_eq!(
+            table.try_row(4).unwrap_err().to_string(),
+            "Row out of range: 4"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn variables() -> Result<()> {
+        let result = TruthTable::try_from(PermutationIter::try_new("A + C => B")?)?;
+        assert_eq!(result.variables(), &['A', 'B', 'C']);
+        Ok(())
+    }
+
+    #[test]
+    fn extend_with_variable() -> Result<()> {
+        let table = TruthTable::try_from(PermutationIter::try_new("A => B")?)?;
+        let result = table.extend_with_variable('C');
+        assert_eq!(result.variables, vec!['A', 'B', 'C']);
+        assert_eq!(
+            result.results,
+            vec![true, true, true, true, false, false, true, true]
+        );
+        assert_eq!(
+            result,
+            TruthTable::try_from(PermutationIter::try_new("A => B | (C + 0)")?)?
+        );
+        assert_eq!(result.extend_with_variable('A'), result);
+        Ok(())
+    }
+
     #[test]
     fn error_invalid_rule() {
-        let result = TruthTable::try_from(PermutationIter::new("A = Z"));
+        let result = PermutationIter::try_new("A = Z").and_then(TruthTable::try_from);
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err().to_string(),
@@ -498,6 +1614,111 @@ mod tests_rule_map {
         Ok(())
     }
 
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn try_from_parallel() -> Result<()> {
+        let rules = vec![
+            "A => B",
+            "B + C => D",
+            "D | !A => E",
+            "E ^ B => F",
+            "A => B",
+        ];
+        let sequential = RuleMap::try_from(rules.clone())?;
+        for threads in [1, 2, 4, 8] {
+            let parallel = RuleMap::try_from_parallel(rules.clone(), threads, &CancelFlag::new())?;
+            assert_eq!(parallel.map, sequential.map);
+            let order: Vec<&str> = parallel.timings().iter().map(|(r, _)| r.as_str()).collect();
+            assert_eq!(order, rules);
+        }
+        let result = RuleMap::try_from_parallel(vec!["A => B", "A =>"], 2, &CancelFlag::new());
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn render_stable() -> Result<()> {
+        let rules = vec!["C => D", "A + B => C", "B => A", "A + B => C"];
+        let rendered = RuleMap::try_from(rules.clone())?.render(TableFormat::Text);
+        for _ in 0..10 {
+            let map = RuleMap::try_from_parallel(rules.clone(), 2, &CancelFlag::new())?;
+            assert_eq!(map.render(TableFormat::Text), rendered);
+        }
+        let symbols: String = rendered
+            .lines()
+            .filter(|l| l.len() == 1)
+            .collect::<Vec<&str>>()
+            .concat();
+        assert_eq!(symbols, "ABCD");
+        Ok(())
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    #[test]
+    fn error_thread_panic() {
+        let build: fn(&str) -> Result<(TruthTable, Duration)> = |rule| {
+            if rule == "B => C" {
+                panic!("Injected panic");
+            }
+            build_table(rule)
+        };
+        let result = RuleMap::build_parallel_with(
+            vec!["A => B", "B => C", "C => D", "D => E"],
+            2,
+            &CancelFlag::new(),
+            build,
+        );
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Truth table thread panicked: 'Injected panic'"
+        );
+    }
+
+    #[test]
+    fn thread_count() {
+        assert_eq!(super::thread_count(Some(1)), 1);
+        assert!(super::thread_count(None) >= 1);
+        assert!(super::thread_count(Some(2)) <= 2);
+    }
+
+    #[test]
+    fn rows_to_confirm() -> Result<()> {
+        let wide = format!(
+            "{}=>Z",
+            ('A'..='Y').map(String::from).collect::<Vec<_>>().join("+")
+        );
+        let rules = vec!["A => B", wide.as_str()];
+        assert_eq!(
+            super::rows_to_confirm(&rules, ROW_LIMIT, false)?,
+            Some(1 << 26)
+        );
+        assert_eq!(super::rows_to_confirm(&rules, ROW_LIMIT, true)?, None);
+        assert_eq!(super::rows_to_confirm(&["A => B"], ROW_LIMIT, false)?, None);
+        assert_eq!(super::rows_to_confirm(&["A + A => B"], 2, false)?, Some(4));
+        Ok(())
+    }
+
+    #[test]
+    fn try_from_cancellable() -> Result<()> {
+        let cancel = CancelFlag::new();
+        let result = RuleMap::try_from_cancellable(vec!["A => B", "B => C"], &cancel)?;
+        assert_eq!(result.map.len(), 3);
+        cancel.cancel();
+        let result = RuleMap::try_from_cancellable(vec!["A => B", "B => C"], &cancel)?;
+        assert!(result.map.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn timings() -> Result<()> {
+        let result = RuleMap::try_from(vec!["A => B", "B => C", "A => B"])?;
+        let rules: Vec<&str> = result.timings().iter().map(|(r, _)| r.as_str()).collect();
+        assert_eq!(rules, vec!["A => B", "B => C", "A => B"]);
+        Ok(())
+    }
+
     #[test]
     fn error_invalid_rule() {
         let result = RuleMap::try_from(vec!["A =>"]);
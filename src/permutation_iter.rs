@@ -1,7 +1,9 @@
 use crate::*;
+use parser::RuleParser;
 
+use anyhow::{anyhow, Context, Result};
 use std::borrow::Borrow;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 // PermutationIter is an iterator that iterates over all permutations of a rule input string
 // The order in which the permutations are generated is always following the same pattern, example:
@@ -14,10 +16,14 @@ pub struct PermutationIter {
     formula: String,
     pub variables: Vec<char>,
     size: usize,
+    permutations: usize,
 }
 
 impl PermutationIter {
-    pub fn new<T>(formula: T) -> PermutationIter
+    // Fails when the number of permutations `1 << variables.len()` doesn't fit in a usize. Only
+    // the identifiers A to Z are variables, the internal symbols of a SymbolTable are left for the
+    // tokenizer to reject.
+    pub fn try_new<T>(formula: T) -> Result<PermutationIter>
     where
         T: Borrow<str>,
     {
@@ -28,64 +34,149 @@ impl PermutationIter {
             .filter(|c| is_identifier(c) && set.insert(c.to_owned()))
             .collect::<Vec<char>>();
         variables.sort_unstable();
-        PermutationIter {
+        Ok(PermutationIter {
             formula,
+            permutations: permutation_count(variables.len())?,
             variables,
             size: 0,
+        })
+    }
+
+    // Returns the permutation at the index, the one `next` yields after skipping `index`
+    // permutations. Doesn't advance the iterator.
+    pub fn permutation(&self, index: usize) -> String {
+        let mut permutation = self.formula.clone();
+        for (i, c) in self.variables.iter().enumerate() {
+            permutation = permutation.replace(
+                &c.to_string(),
+                if index & (1 << (self.variables.len() - 1 - i)) == 0 {
+                    "0"
+                } else {
+                    "1"
+                },
+            );
         }
+        permutation
+    }
+
+    // Returns the value of every variable in the permutation at the index.
+    fn assignment(&self, index: usize) -> HashMap<char, bool> {
+        let len = self.variables.len();
+        self.variables
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (*c, index & (1 << (len - 1 - i)) != 0))
+            .collect()
+    }
+
+    // Lazily yields only the remaining permutations for which the formula is true. A formula
+    // without an implicator is evaluated as an expression, see RuleParser::parse_formula. The
+    // formula is parsed once, before iterating, and fails when it is invalid.
+    pub fn filter_true(self) -> Result<impl Iterator<Item = String>> {
+        let node = RuleParser::new()
+            .parse_formula(&self.formula)
+            .context(format!("Failed to parse formula: '{}'", self.formula))?;
+        Ok((self.size..self.permutations).filter_map(move |index| {
+            let result = node
+                .eval(&self.assignment(index))
+                .expect("Every variable of the formula is assigned");
+            if result {
+                Some(self.permutation(index))
+            } else {
+                None
+            }
+        }))
     }
 }
 
+// The largest number of variables of which all permutations can be counted in a usize.
+pub const MAX_VARIABLES: usize = usize::BITS as usize - 1;
+
+pub fn check_variable_count(count: usize) -> Result<()> {
+    if count > MAX_VARIABLES {
+        return Err(anyhow!(
+            "formula has {} variables; maximum supported is {}",
+            count,
+            MAX_VARIABLES
+        ));
+    }
+    Ok(())
+}
+
+// Returns the number of permutations of the variables, `1 << count`, failing when it doesn't fit
+// in a usize.
+pub fn permutation_count(count: usize) -> Result<usize> {
+    check_variable_count(count)?;
+    u32::try_from(count)
+        .ok()
+        .and_then(|count| 1usize.checked_shl(count))
+        .context(format!("Too many permutations of {} variables", count))
+}
+
 impl Iterator for PermutationIter {
     type Item = String;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.size == 1 << self.variables.len() {
+        if self.size == self.permutations {
             None
         } else {
-            let mut permutation = self.formula.clone();
-            for (i, c) in self.variables.iter().enumerate() {
-                permutation = permutation.replace(
-                    &c.to_string(),
-                    if self.size & (1 << (self.variables.len() - 1 - i)) == 0 {
-                        "0"
-                    } else {
-                        "1"
-                    },
-                );
-            }
+            let permutation = self.permutation(self.size);
             self.size += 1;
             Some(permutation)
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.permutations - self.size;
+        (remaining, Some(remaining))
+    }
 }
 
+// Before iterating, `len` is the total number of permutations: `1 << variables.len()`.
+impl ExactSizeIterator for PermutationIter {}
+
 #[cfg(test)]
 mod tests_permutation_iter {
     use super::*;
 
+    use anyhow::Result;
     use pretty_assertions::assert_eq;
 
     #[test]
-    fn empty() {
-        let mut iter = PermutationIter::new("");
+    fn empty() -> Result<()> {
+        let mut iter = PermutationIter::try_new("")?;
         assert_eq!(Some("".to_string()), iter.next());
         assert_eq!(None, iter.next());
+        Ok(())
+    }
+
+    #[test]
+    fn constant() -> Result<()> {
+        // Without variables there is a single permutation, the formula itself.
+        let iter = PermutationIter::try_new("1 => 0")?;
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.permutation(0), "1 => 0");
+        assert_eq!(iter.collect::<Vec<_>>(), vec!["1 => 0"]);
+        let rows: Vec<String> = PermutationIter::try_new("1")?.filter_true()?.collect();
+        assert_eq!(rows, vec!["1"]);
+        assert_eq!(PermutationIter::try_new("0 | 0")?.filter_true()?.count(), 0);
+        Ok(())
     }
 
     #[test]
-    fn identifiers() {
-        let mut iter = PermutationIter::new("! 1 0 , . a z A Z");
+    fn identifiers() -> Result<()> {
+        let mut iter = PermutationIter::try_new("! 1 0 , . a z A Z")?;
         assert_eq!(Some("! 1 0 , . a z 0 0".to_string()), iter.next());
         assert_eq!(Some("! 1 0 , . a z 0 1".to_string()), iter.next());
         assert_eq!(Some("! 1 0 , . a z 1 0".to_string()), iter.next());
         assert_eq!(Some("! 1 0 , . a z 1 1".to_string()), iter.next());
         assert_eq!(None, iter.next());
+        Ok(())
     }
 
     #[test]
-    fn order() {
-        let mut iter = PermutationIter::new("A B C");
+    fn order() -> Result<()> {
+        let mut iter = PermutationIter::try_new("A B C")?;
         assert_eq!(Some("0 0 0".to_string()), iter.next());
         assert_eq!(Some("0 0 1".to_string()), iter.next());
         assert_eq!(Some("0 1 0".to_string()), iter.next());
@@ -95,21 +186,36 @@ mod tests_permutation_iter {
         assert_eq!(Some("1 1 0".to_string()), iter.next());
         assert_eq!(Some("1 1 1".to_string()), iter.next());
         assert_eq!(None, iter.next());
+        Ok(())
+    }
+
+    #[test]
+    fn permutation() -> Result<()> {
+        let iter = PermutationIter::try_new("A B C")?;
+        assert_eq!(iter.permutation(5), "1 0 1");
+        assert_eq!(
+            (0..iter.len())
+                .map(|i| iter.permutation(i))
+                .collect::<Vec<_>>(),
+            iter.collect::<Vec<_>>()
+        );
+        Ok(())
     }
 
     #[test]
-    fn duplicate_identifiers() {
-        let mut iter = PermutationIter::new("A A B B");
+    fn duplicate_identifiers() -> Result<()> {
+        let mut iter = PermutationIter::try_new("A A B B")?;
         assert_eq!(Some("0 0 0 0".to_string()), iter.next());
         assert_eq!(Some("0 0 1 1".to_string()), iter.next());
         assert_eq!(Some("1 1 0 0".to_string()), iter.next());
         assert_eq!(Some("1 1 1 1".to_string()), iter.next());
         assert_eq!(None, iter.next());
+        Ok(())
     }
 
     #[test]
-    fn with_rule_symbols() {
-        let mut iter = PermutationIter::new("A + B <=> C");
+    fn with_rule_symbols() -> Result<()> {
+        let mut iter = PermutationIter::try_new("A + B <=> C")?;
         assert_eq!(Some("0 + 0 <=> 0".to_string()), iter.next());
         assert_eq!(Some("0 + 0 <=> 1".to_string()), iter.next());
         assert_eq!(Some("0 + 1 <=> 0".to_string()), iter.next());
@@ -119,13 +225,118 @@ mod tests_permutation_iter {
         assert_eq!(Some("1 + 1 <=> 0".to_string()), iter.next());
         assert_eq!(Some("1 + 1 <=> 1".to_string()), iter.next());
         assert_eq!(None, iter.next());
+        Ok(())
+    }
+
+    #[test]
+    fn len() -> Result<()> {
+        for formula in ["", "A", "A => B", "A + B <=> C", "A | B | C | D => A"] {
+            let iter = PermutationIter::try_new(formula)?;
+            let len = iter.len();
+            assert_eq!(len, 1 << iter.variables.len());
+            assert_eq!(len, iter.count());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn len_remaining() -> Result<()> {
+        let mut iter = PermutationIter::try_new("A B")?;
+        assert_eq!(iter.len(), 4);
+        iter.next();
+        assert_eq!(iter.len(), 3);
+        iter.by_ref().for_each(drop);
+        assert_eq!(iter.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn try_new() -> Result<()> {
+        let iter = PermutationIter::try_new("A + B => C")?;
+        assert_eq!(iter.len(), 8);
+        Ok(())
+    }
+
+    #[test]
+    fn error_variable_count() {
+        assert!(check_variable_count(MAX_VARIABLES).is_ok());
+        let result = check_variable_count(MAX_VARIABLES + 1);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                "formula has {} variables; maximum supported is {}",
+                MAX_VARIABLES + 1,
+                MAX_VARIABLES
+            )
+        );
+    }
+
+    #[test]
+    fn error_too_many_variables() -> Result<()> {
+        assert_eq!(permutation_count(MAX_VARIABLES)?, 1 << MAX_VARIABLES);
+        let result = permutation_count(symbol_table::MAX_SYMBOLS);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            format!(
+                "formula has {} variables; maximum supported is {}",
+                symbol_table::MAX_SYMBOLS,
+                MAX_VARIABLES
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_internal_symbols() -> Result<()> {
+        use parser::TruthTable;
+
+        let iter = PermutationIter::try_new("A => \u{E000}")?;
+        assert_eq!(iter.variables, vec!['A']);
+        let result = TruthTable::try_from(iter);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Failed to evaluate permutation 0 => \u{E000}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn filter_true() -> Result<()> {
+        let rows: Vec<String> = PermutationIter::try_new("A+B")?.filter_true()?.collect();
+        assert_eq!(rows, vec!["1+1".to_string()]);
+        let rows: Vec<String> = PermutationIter::try_new("A => B")?.filter_true()?.collect();
+        assert_eq!(rows, vec!["0 => 0", "0 => 1", "1 => 1"]);
+        let rows: Vec<String> = PermutationIter::try_new("A -> B + C")?
+            .filter_true()?
+            .collect();
+        assert_eq!(rows.len(), 5);
+        let mut iter = PermutationIter::try_new("A | B")?;
+        iter.next();
+        let rows: Vec<String> = iter.filter_true()?.collect();
+        assert_eq!(rows, vec!["0 | 1", "1 | 0", "1 | 1"]);
+        Ok(())
+    }
+
+    #[test]
+    fn error_filter_true() -> Result<()> {
+        let result = PermutationIter::try_new("A + => B")?.filter_true();
+        assert!(result.is_err());
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Failed to parse formula: 'A + => B'"
+        );
+        Ok(())
     }
 
     #[test]
-    fn respect_white_space() {
-        let mut iter = PermutationIter::new("\t\n\r A");
+    fn respect_white_space() -> Result<()> {
+        let mut iter = PermutationIter::try_new("\t\n\r A")?;
         assert_eq!(Some("\t\n\r 0".to_string()), iter.next());
         assert_eq!(Some("\t\n\r 1".to_string()), iter.next());
         assert_eq!(None, iter.next());
+        Ok(())
     }
 }
@@ -0,0 +1,24 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::path::Path;
+
+// Samples per second taken of the running thread.
+const SAMPLE_FREQUENCY: i32 = 1000;
+
+// Calls `f` while sampling its stack and writes the samples as a flamegraph SVG to `output`. A run
+// too short to take a single sample leaves the file empty.
+pub fn profile<T>(output: &Path, f: impl FnOnce() -> T) -> Result<T> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(SAMPLE_FREQUENCY)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .context("Unable to start profiler")?;
+    let result = f();
+    let report = guard.report().build().context("Unable to build profile")?;
+    let file = File::create(output)
+        .context(format!("Unable to create profile: '{}'", output.display()))?;
+    report
+        .flamegraph(file)
+        .context(format!("Unable to write profile: '{}'", output.display()))?;
+    Ok(result)
+}
@@ -0,0 +1,871 @@
+use crate::*;
+use node::Node;
+use operator::OperatorSet;
+use parser::{Direction, ImplicatorStyle, RuleParser, Token};
+
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+// Rule holds a single parsed line of the rules section. A rule may be prefixed with a label, e.g.
+// `r1: A => B`, which names the rule in the output, and with a priority annotation, e.g.
+// `[10] A => B`, which is used to resolve conflicts between rules.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub label: Option<String>,
+    pub formula: String,
+    pub priority: i32,
+    pub direction: Direction,
+    pub premise: Node,
+    pub conclusion: Node,
+    // A disabled rule is skipped when solving, see set_enabled.
+    pub enabled: bool,
+}
+
+impl Rule {
+    // Returns the (premise, conclusion) pairs of the rule, a biconditional rule holds both ways. A
+    // disabled rule holds neither way, so solving skips it.
+    pub fn implications(&self) -> impl Iterator<Item = (&Node, &Node)> {
+        let forward = self.enabled.then_some((&self.premise, &self.conclusion));
+        let backward = (self.enabled && self.direction == Direction::BiDirectional)
+            .then_some((&self.conclusion, &self.premise));
+        forward.into_iter().chain(backward)
+    }
+
+    // Returns the rule in canonical form, e.g. `r1: [10] A + B => C`.
+    pub fn canonical(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{}: {}", label, self.unlabelled()),
+            None => self.unlabelled(),
+        }
+    }
+
+    // Returns the formula of the rule read as an equivalence, e.g. `A + B <=> C` for `A + B => C`.
+    // Only used to compare truth tables, solving always reads a rule as written.
+    pub fn as_equivalence(&self) -> String {
+        Node::Implication(
+            Direction::BiDirectional,
+            Box::new(self.premise.clone()),
+            Box::new(self.conclusion.clone()),
+        )
+        .to_string()
+    }
+
+    // Returns the canonical form without the label, rules only differing in their label are
+    // equal.
+    fn unlabelled(&self) -> String {
+        let node = Node::Implication(
+            self.direction,
+            Box::new(self.premise.clone()),
+            Box::new(self.conclusion.clone()),
+        );
+        match self.priority {
+            0 => node.to_string(),
+            priority => format!("[{}] {}", priority, node),
+        }
+    }
+
+    // Returns the number of premise symbols, a rule with a stronger premise is more specific. Breaks
+    // ties between conflicting rules of the same priority.
+    pub fn specificity(&self) -> usize {
+        self.premise.symbols().len()
+    }
+
+    // Returns the height of the rule as a tree, the implication being the root.
+    pub fn depth(&self) -> usize {
+        1 + self.premise.depth().max(self.conclusion.depth())
+    }
+
+    // Returns the number of nodes of the rule as a tree, the implication included.
+    pub fn node_count(&self) -> usize {
+        1 + self.premise.node_count() + self.conclusion.node_count()
+    }
+
+    pub fn symbols(&self) -> BTreeSet<char> {
+        let mut symbols = self.premise.symbols();
+        symbols.append(&mut self.conclusion.symbols());
+        symbols
+    }
+}
+
+// RuleError is returned for a rule that doesn't have exactly one top-level implicator, or that has
+// nothing on one of its sides, so callers can tell these apart from other syntax errors by
+// downcasting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    MissingPremise(String),
+    MissingConclusion(String),
+    NoImplication(String),
+    MultipleImplications(String),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::MissingPremise(rule) => write!(f, "rule '{}' is missing a premise", rule),
+            RuleError::MissingConclusion(rule) => {
+                write!(f, "rule '{}' is missing a conclusion", rule)
+            }
+            RuleError::NoImplication(rule) => write!(f, "rule '{}' has no implication", rule),
+            RuleError::MultipleImplications(rule) => {
+                write!(f, "rule '{}' has multiple implications", rule)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+// Checks that the formula has exactly one implicator outside parentheses and that both its sides
+// hold at least one token, tokenized by the parser the rule is parsed with. Formulas that fail to
+// tokenize are left for the parser to report.
+fn check_sides(line: &str, parser: &mut RuleParser, formula: &str) -> Result<(), RuleError> {
+    let Ok(token_list) = parser.tokenize(formula) else {
+        return Ok(());
+    };
+    let mut depth = 0;
+    let mut implicators = vec![];
+    for (i, token) in token_list.iter().enumerate() {
+        match token {
+            Token::Parenthesis('(') => depth += 1,
+            Token::Parenthesis(_) => depth -= 1,
+            Token::Implicator(_) if depth == 0 => implicators.push(i),
+            _ => {}
+        }
+    }
+    match implicators[..] {
+        [] => Err(RuleError::NoImplication(line.to_string())),
+        [0] => Err(RuleError::MissingPremise(line.to_string())),
+        [i] if i + 1 == token_list.len() => Err(RuleError::MissingConclusion(line.to_string())),
+        [_] => Ok(()),
+        _ => Err(RuleError::MultipleImplications(line.to_string())),
+    }
+}
+
+// Rules are compared by their canonical form, so rules that only differ in spacing or redundant
+// parentheses are equal, while `A + B => C` and `B + A => C` are not.
+impl PartialEq for Rule {
+    fn eq(&self, other: &Self) -> bool {
+        self.unlabelled() == other.unlabelled()
+    }
+}
+
+impl Eq for Rule {}
+
+// Ranks rules by specificity, different rules of the same specificity are not comparable.
+impl PartialOrd for Rule {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        if self == other {
+            return Some(std::cmp::Ordering::Equal);
+        }
+        match self.specificity().cmp(&other.specificity()) {
+            std::cmp::Ordering::Equal => None,
+            ordering => Some(ordering),
+        }
+    }
+}
+
+impl Hash for Rule {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.unlabelled().hash(state);
+    }
+}
+
+// RuleIndex maps every symbol to the rules that have it in a conclusion, so the rules concluding a
+// symbol are found without scanning all rules.
+pub struct RuleIndex {
+    by_conclusion: HashMap<char, Vec<usize>>,
+}
+
+impl RuleIndex {
+    pub fn new(rules: &[Rule]) -> Self {
+        let mut by_conclusion: HashMap<char, Vec<usize>> = HashMap::new();
+        for (i, rule) in rules.iter().enumerate() {
+            let mut symbols = BTreeSet::new();
+            for (_, conclusion) in rule.implications() {
+                symbols.append(&mut conclusion.symbols());
+            }
+            for c in symbols {
+                by_conclusion.entry(c).or_default().push(i);
+            }
+        }
+        RuleIndex { by_conclusion }
+    }
+
+    // Returns the indices of the rules concluding the symbol, in rule order.
+    pub fn concluding(&self, symbol: char) -> &[usize] {
+        self.by_conclusion
+            .get(&symbol)
+            .map_or(&[], |v| v.as_slice())
+    }
+}
+
+// Returns the rules that can transitively contribute to one of the queried symbols, found by
+// walking backwards from the queries through the rules concluding them.
+pub fn relevant_rules<'a>(rules: &'a [Rule], queries: &str) -> Vec<&'a Rule> {
+    let (relevant, _) = relevant_flags(rules, queries, Some(&RuleIndex::new(rules)));
+    rules
+        .iter()
+        .zip(relevant)
+        .filter_map(|(rule, relevant)| relevant.then_some(rule))
+        .collect()
+}
+
+// Marks the relevant rules, returns the flags along with the number of rules that were looked at.
+// Without an index all rules are scanned until no more rules become relevant.
+fn relevant_flags(rules: &[Rule], queries: &str, index: Option<&RuleIndex>) -> (Vec<bool>, usize) {
+    let mut relevant = vec![false; rules.len()];
+    let mut scans = 0;
+    let mut symbols: BTreeSet<char> = queries.chars().collect();
+    match index {
+        Some(index) => {
+            let mut pending: Vec<char> = symbols.iter().copied().collect();
+            while let Some(symbol) = pending.pop() {
+                for i in index.concluding(symbol) {
+                    scans += 1;
+                    if !relevant[*i] {
+                        relevant[*i] = true;
+                        pending.extend(
+                            rules[*i]
+                                .symbols()
+                                .into_iter()
+                                .filter(|c| symbols.insert(*c)),
+                        );
+                    }
+                }
+            }
+        }
+        None => loop {
+            let mut grew = false;
+            for (i, rule) in rules.iter().enumerate() {
+                if relevant[i] {
+                    continue;
+                }
+                scans += 1;
+                if rule
+                    .implications()
+                    .any(|(_, conclusion)| !conclusion.symbols().is_disjoint(&symbols))
+                {
+                    relevant[i] = true;
+                    symbols.append(&mut rule.symbols());
+                    grew = true;
+                }
+            }
+            if !grew {
+                break;
+            }
+        },
+    }
+    (relevant, scans)
+}
+
+// Rewrites every biconditional rule into its two implications, e.g. `A <=> B` into `A => B` and
+// `B => A`, keeping the priority and whether the rule is enabled. Labels are unique, so the label
+// stays on the first implication only. Other rules are kept as they are.
+pub fn expand_biconditionals(rules: &[Rule]) -> Vec<Rule> {
+    let mut expanded = vec![];
+    for rule in rules.iter() {
+        match rule.direction {
+            Direction::UniDirectional => expanded.push(rule.clone()),
+            Direction::BiDirectional => {
+                let implications = [
+                    (rule.label.clone(), &rule.premise, &rule.conclusion),
+                    (None, &rule.conclusion, &rule.premise),
+                ];
+                for (label, premise, conclusion) in implications {
+                    let node = Node::Implication(
+                        Direction::UniDirectional,
+                        Box::new(premise.clone()),
+                        Box::new(conclusion.clone()),
+                    );
+                    expanded.push(Rule {
+                        label,
+                        formula: sanitize::remove_spaces(node.to_string()),
+                        priority: rule.priority,
+                        direction: Direction::UniDirectional,
+                        premise: premise.clone(),
+                        conclusion: conclusion.clone(),
+                        enabled: rule.enabled,
+                    });
+                }
+            }
+        }
+    }
+    expanded
+}
+
+// Shuffles the rules deterministically for the given seed, using a splitmix64 generator.
+pub fn shuffle_rules(rules: &mut [Rule], seed: u64) {
+    let mut state = seed;
+    for i in (1..rules.len()).rev() {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        rules.swap(i, (z % (i as u64 + 1)) as usize);
+    }
+}
+
+// Returns the key sort_rules orders on: the conclusion and then the premise in canonical form,
+// followed by the direction and the priority.
+pub fn sort_key(rule: &Rule) -> (String, String, bool, i32) {
+    (
+        rule.conclusion.to_string(),
+        rule.premise.to_string(),
+        rule.direction == Direction::BiDirectional,
+        rule.priority,
+    )
+}
+
+// Sorts the rules into a canonical order that doesn't depend on their order in the input.
+pub fn sort_rules(rules: &mut [Rule]) {
+    rules.sort_by_cached_key(sort_key);
+}
+
+impl FromStr for Rule {
+    type Err = anyhow::Error;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        Rule::parse_with_style(line, ImplicatorStyle::default())
+    }
+}
+
+impl Rule {
+    // Parses a rule, only accepting the implicator spellings of the given style.
+    pub fn parse_with_style(line: &str, style: ImplicatorStyle) -> Result<Self> {
+        Rule::parse_with_operators(line, style, OperatorSet::default())
+    }
+
+    // Parses a rule written with the operators of the given set. The formula is kept with the
+    // operators of the default set and without the spaces the tokenizer skipped, so its truth
+    // table can be built like that of any other rule.
+    pub fn parse_with_operators(
+        line: &str,
+        style: ImplicatorStyle,
+        operators: OperatorSet,
+    ) -> Result<Self> {
+        let parser = RuleParser::with_style(style).with_operators(operators);
+        Rule::parse_with_parser(line, parser, operators)
+    }
+
+    // Parses a rule translated by a SymbolTable, which can hold the internal symbols past Z.
+    pub fn parse_translated(line: &str) -> Result<Self> {
+        let parser = RuleParser::new().with_internal_symbols();
+        Rule::parse_with_parser(line, parser, OperatorSet::default())
+    }
+
+    fn parse_with_parser(
+        line: &str,
+        mut parser: RuleParser,
+        operators: OperatorSet,
+    ) -> Result<Self> {
+        let (label, rest) = match line.split_once(':') {
+            Some((label, rest)) => {
+                let label = label.trim();
+                if label.is_empty() || !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    return Err(anyhow!("Invalid rule label: '{}'", label));
+                }
+                (Some(label.to_string()), rest)
+            }
+            None => (None, line),
+        };
+        let (priority, formula) = match rest.trim_start().strip_prefix('[') {
+            Some(rest) => {
+                let (priority, formula) = rest.split_once(']').context(format!(
+                    "Missing closing bracket in rule priority: '{}'",
+                    line
+                ))?;
+                let priority = priority
+                    .trim()
+                    .parse()
+                    .context(format!("Invalid rule priority: '{}'", priority))?;
+                (priority, formula.trim())
+            }
+            None => (0, rest.trim()),
+        };
+        check_sides(line, &mut parser, formula)?;
+        match parser
+            .parse(formula)
+            .context(format!("Failed to parse rule: '{}'", line))?
+        {
+            Node::Implication(direction, premise, conclusion) => Ok(Rule {
+                label,
+                formula: sanitize::remove_spaces(operators.to_default(formula)),
+                priority,
+                direction,
+                premise: *premise,
+                conclusion: *conclusion,
+                enabled: true,
+            }),
+            _ => Err(anyhow!("No implicator found")),
+        }
+    }
+}
+
+// A labelled rule is displayed as its label, other rules as their formula.
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "{}", label),
+            None => write!(f, "{}", self.formula),
+        }
+    }
+}
+
+// Checks that no two rules have the same label.
+pub fn check_labels(rules: &[Rule]) -> Result<()> {
+    let mut labels = BTreeSet::new();
+    for label in rules.iter().filter_map(|r| r.label.as_ref()) {
+        if !labels.insert(label) {
+            return Err(anyhow!("Duplicate rule label: '{}'", label));
+        }
+    }
+    Ok(())
+}
+
+// Enables or disables the rule with the label, to see what solving gives without it.
+pub fn set_enabled(rules: &mut [Rule], label: &str, enabled: bool) -> Result<()> {
+    let rule = rules
+        .iter_mut()
+        .find(|r| r.label.as_deref() == Some(label))
+        .context(format!("Unknown rule label: '{}'", label))?;
+    rule.enabled = enabled;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_rule {
+    use super::*;
+    use std::cmp::Ordering;
+    use std::collections::HashSet;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn simple() -> Result<()> {
+        let rule: Rule = "A+B=>C".parse()?;
+        assert_eq!(rule.formula, "A+B=>C");
+        assert_eq!(rule.priority, 0);
+        assert_eq!(rule.direction, Direction::UniDirectional);
+        assert_eq!(rule.symbols(), BTreeSet::from(['A', 'B', 'C']));
+        Ok(())
+    }
+
+    #[test]
+    fn priority() -> Result<()> {
+        let rule: Rule = "[10]A=>B".parse()?;
+        assert_eq!(rule.formula, "A=>B");
+        assert_eq!(rule.priority, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn negative_priority() -> Result<()> {
+        let rule: Rule = "[-3]A<=>B".parse()?;
+        assert_eq!(rule.priority, -3);
+        assert_eq!(rule.direction, Direction::BiDirectional);
+        Ok(())
+    }
+
+    #[test]
+    fn label() -> Result<()> {
+        let rule: Rule = "r1:[10]A=>B".parse()?;
+        assert_eq!(rule.label, Some("r1".to_string()));
+        assert_eq!(rule.formula, "A=>B");
+        assert_eq!(rule.priority, 10);
+        assert_eq!(rule.to_string(), "r1");
+        assert_eq!(rule.canonical(), "r1: [10] A => B");
+        assert_eq!(rule, "[10]A=>B".parse()?);
+
+        let rule: Rule = "A=>B".parse()?;
+        assert_eq!(rule.label, None);
+        assert_eq!(rule.to_string(), "A=>B");
+        Ok(())
+    }
+
+    #[test]
+    fn check_labels() -> Result<()> {
+        let rules = ["r1:A=>B", "B=>C", "r2:C=>D"]
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<Vec<Rule>>>()?;
+        super::check_labels(&rules)?;
+
+        let rules = ["r1:A=>B", "B=>C", "r1:C=>D"]
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<Vec<Rule>>>()?;
+        assert_eq!(
+            super::check_labels(&rules).unwrap_err().to_string(),
+            "Duplicate rule label: 'r1'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn as_equivalence() -> Result<()> {
+        use parser::TruthTable;
+        use permutation_iter::PermutationIter;
+
+        let rule: Rule = "A+B=>!C".parse()?;
+        assert_eq!(rule.as_equivalence(), "A + B <=> !C");
+        assert_eq!(
+            TruthTable::try_from(PermutationIter::try_new(rule.as_equivalence())?)?,
+            TruthTable::try_from(PermutationIter::try_new("A+B<=>!C")?)?
+        );
+        let rule: Rule = "A<=>B".parse()?;
+        assert_eq!(rule.as_equivalence(), "A <=> B");
+        Ok(())
+    }
+
+    #[test]
+    fn set_enabled() -> Result<()> {
+        let mut rules = ["r1:A=>B", "B=>C"]
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<Vec<Rule>>>()?;
+        super::set_enabled(&mut rules, "r1", false)?;
+        assert!(!rules[0].enabled);
+        assert_eq!(rules[0].implications().count(), 0);
+        super::set_enabled(&mut rules, "r1", true)?;
+        assert_eq!(rules[0].implications().count(), 1);
+        assert_eq!(
+            super::set_enabled(&mut rules, "r2", false)
+                .unwrap_err()
+                .to_string(),
+            "Unknown rule label: 'r2'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn specificity() -> Result<()> {
+        let specific: Rule = "A+B=>Z".parse()?;
+        let general: Rule = "A=>Z".parse()?;
+        assert_eq!(specific.specificity(), 2);
+        assert_eq!(general.specificity(), 1);
+        assert!(specific > general);
+        assert!(general < specific);
+        assert_eq!(
+            specific.partial_cmp(&"A + B => Z".parse()?),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(specific.partial_cmp(&"A|B=>Z".parse()?), None);
+        assert_eq!("A+A=>Z".parse::<Rule>()?.specificity(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn canonical() -> Result<()> {
+        let rule: Rule = "[10](A+B)|!C=>D".parse()?;
+        assert_eq!(rule.canonical(), "[10] A + B | !C => D");
+        let rule: Rule = "A<=>B^C".parse()?;
+        assert_eq!(rule.canonical(), "A <=> B ^ C");
+        Ok(())
+    }
+
+    #[test]
+    fn thin_arrow() -> Result<()> {
+        let rule: Rule = "A->B".parse()?;
+        assert_eq!(rule, "A=>B".parse()?);
+        assert_eq!(rule.canonical(), "A => B");
+        let result = Rule::parse_with_style("A->B", ImplicatorStyle::Fat);
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn c_style_operators() -> Result<()> {
+        let c_style = OperatorSet::c_style();
+        let rule = Rule::parse_with_operators("r1:[2]A&~B=>C", ImplicatorStyle::Both, c_style)?;
+        assert_eq!(rule, "r1:[2]A+!B=>C".parse()?);
+        assert_eq!(rule.formula, "A+!B=>C");
+        let result = Rule::parse_with_operators("A+B=>C", ImplicatorStyle::Both, c_style);
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn hash_set() -> Result<()> {
+        let rules: HashSet<Rule> = [
+            "A+B=>C",
+            "(A + B) => C",
+            "[0]A+B=>((C))",
+            "B+A=>C",
+            "[1]A+B=>C",
+        ]
+        .iter()
+        .map(|r| r.parse())
+        .collect::<Result<_>>()?;
+        let mut canonical: Vec<String> = rules.iter().map(|r| r.canonical()).collect();
+        canonical.sort();
+        assert_eq!(
+            canonical,
+            vec!["A + B => C", "B + A => C", "[1] A + B => C"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn implications() -> Result<()> {
+        let rule: Rule = "A<=>B".parse()?;
+        let implications: Vec<(&Node, &Node)> = rule.implications().collect();
+        assert_eq!(implications.len(), 2);
+        assert_eq!(
+            implications[1],
+            (&Node::Identifier('B'), &Node::Identifier('A'))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn relevant() -> Result<()> {
+        let rules: Vec<Rule> = ["A=>B", "C=>D", "B+E=>Z", "D<=>F", "Z=>G"]
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<_>>()?;
+        let relevant: Vec<&str> = relevant_rules(&rules, "Z")
+            .iter()
+            .map(|r| r.formula.as_str())
+            .collect();
+        assert_eq!(relevant, vec!["A=>B", "B+E=>Z"]);
+        let relevant: Vec<&str> = relevant_rules(&rules, "F")
+            .iter()
+            .map(|r| r.formula.as_str())
+            .collect();
+        assert_eq!(relevant, vec!["C=>D", "D<=>F"]);
+        Ok(())
+    }
+
+    #[test]
+    fn expand() -> Result<()> {
+        let rules: Vec<Rule> = ["(A+B)<=>C", "D=>E"]
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<_>>()?;
+        let expanded = expand_biconditionals(&rules);
+        let formulas: Vec<&str> = expanded.iter().map(|r| r.formula.as_str()).collect();
+        assert_eq!(formulas, vec!["A+B=>C", "C=>A+B", "D=>E"]);
+
+        let node = |rule: &Rule| {
+            Node::Implication(
+                rule.direction,
+                Box::new(rule.premise.clone()),
+                Box::new(rule.conclusion.clone()),
+            )
+        };
+        for permutation in 0..8 {
+            let state = HashMap::from([
+                ('A', permutation & 1 != 0),
+                ('B', permutation & 2 != 0),
+                ('C', permutation & 4 != 0),
+            ]);
+            assert_eq!(
+                node(&rules[0]).eval(&state)?,
+                node(&expanded[0]).eval(&state)? && node(&expanded[1]).eval(&state)?
+            );
+        }
+
+        // The label stays on the first implication and a disabled rule stays disabled.
+        let mut rules = vec![Rule::from_str("r1: [2] A<=>B")?];
+        super::set_enabled(&mut rules, "r1", false)?;
+        let expanded = expand_biconditionals(&rules);
+        let canonical: Vec<String> = expanded.iter().map(|r| r.canonical()).collect();
+        assert_eq!(canonical, vec!["r1: [2] A => B", "[2] B => A"]);
+        assert!(expanded.iter().all(|r| !r.enabled));
+        Ok(())
+    }
+
+    #[test]
+    fn rule_index() -> Result<()> {
+        let rules: Vec<Rule> = ["A=>B", "C=>B+D", "B<=>E"]
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<_>>()?;
+        let index = RuleIndex::new(&rules);
+        assert_eq!(index.concluding('B'), &[0, 1, 2]);
+        assert_eq!(index.concluding('E'), &[2]);
+        assert_eq!(index.concluding('A'), &[] as &[usize]);
+        Ok(())
+    }
+
+    #[test]
+    fn relevant_scans() -> Result<()> {
+        let mut lines: Vec<String> = (b'A'..=b'Y')
+            .map(|c| format!("{}=>{}", c as char, (c + 1) as char))
+            .collect();
+        lines.reverse();
+        let rules: Vec<Rule> = lines.iter().map(|r| r.parse()).collect::<Result<_>>()?;
+        let (naive, naive_scans) = relevant_flags(&rules, "M", None);
+        let (indexed, indexed_scans) = relevant_flags(&rules, "M", Some(&RuleIndex::new(&rules)));
+        assert_eq!(naive, indexed);
+        assert_eq!(indexed_scans, 12);
+        assert!(indexed_scans < naive_scans);
+        Ok(())
+    }
+
+    #[test]
+    fn sort() -> Result<()> {
+        let rules: Vec<Rule> = ["C=>D", "B=>A", "[1]A=>B", "A=>B", "A<=>B", "!A|C=>A"]
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<_>>()?;
+        let formulas =
+            |rules: &[Rule]| -> Vec<String> { rules.iter().map(|r| r.canonical()).collect() };
+        let mut sorted = rules.clone();
+        sort_rules(&mut sorted);
+        assert_eq!(
+            formulas(&sorted),
+            vec![
+                "!A | C => A",
+                "B => A",
+                "A => B",
+                "[1] A => B",
+                "A <=> B",
+                "C => D"
+            ]
+        );
+        for seed in [1, 7, 42] {
+            let mut shuffled = rules.clone();
+            shuffle_rules(&mut shuffled, seed);
+            sort_rules(&mut shuffled);
+            assert_eq!(formulas(&shuffled), formulas(&sorted));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn shuffle() -> Result<()> {
+        let rules: Vec<Rule> = ["A=>B", "B=>C", "C=>D", "D=>E", "E=>F"]
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<_>>()?;
+        let formulas =
+            |rules: &[Rule]| -> Vec<String> { rules.iter().map(|r| r.formula.clone()).collect() };
+
+        let mut first = rules.clone();
+        shuffle_rules(&mut first, 42);
+        let mut second = rules.clone();
+        shuffle_rules(&mut second, 42);
+        assert_eq!(formulas(&first), formulas(&second));
+
+        let mut sorted = formulas(&first);
+        sorted.sort();
+        assert_eq!(sorted, formulas(&rules));
+        Ok(())
+    }
+
+    #[test]
+    fn error_missing_conclusion() {
+        let result = "A=>".parse::<Rule>();
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "rule 'A=>' is missing a conclusion");
+        assert_eq!(
+            err.downcast_ref::<RuleError>(),
+            Some(&RuleError::MissingConclusion("A=>".to_string()))
+        );
+    }
+
+    #[test]
+    fn error_missing_premise() {
+        let result = "=>Z".parse::<Rule>();
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "rule '=>Z' is missing a premise");
+        assert_eq!(
+            err.downcast_ref::<RuleError>(),
+            Some(&RuleError::MissingPremise("=>Z".to_string()))
+        );
+        let result = "=>".parse::<Rule>();
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "rule '=>' is missing a premise"
+        );
+    }
+
+    #[test]
+    fn error_sides_of_any_parser() {
+        // Thin arrows, c-style operators and internal symbols only tokenize with their parser.
+        let rule_error =
+            |result: Result<Rule>| result.unwrap_err().downcast_ref::<RuleError>().cloned();
+        let line = "A->";
+        let result = Rule::parse_with_style(line, ImplicatorStyle::Thin);
+        assert_eq!(
+            rule_error(result),
+            Some(RuleError::MissingConclusion(line.to_string()))
+        );
+        let line = "A&~B=>";
+        let result =
+            Rule::parse_with_operators(line, ImplicatorStyle::Both, OperatorSet::c_style());
+        assert_eq!(
+            rule_error(result),
+            Some(RuleError::MissingConclusion(line.to_string()))
+        );
+        let line = "\u{E000}=>";
+        let result = Rule::parse_translated(line);
+        assert_eq!(
+            rule_error(result),
+            Some(RuleError::MissingConclusion(line.to_string()))
+        );
+    }
+
+    #[test]
+    fn implicator_count() -> Result<()> {
+        let result = "A+B".parse::<Rule>();
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "rule 'A+B' has no implication");
+        assert_eq!(
+            err.downcast_ref::<RuleError>(),
+            Some(&RuleError::NoImplication("A+B".to_string()))
+        );
+
+        let rule: Rule = "A+B=>C".parse()?;
+        assert_eq!(rule.conclusion.symbols(), BTreeSet::from(['C']));
+
+        let result = "A=>B<=>C".parse::<Rule>();
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert_eq!(err.to_string(), "rule 'A=>B<=>C' has multiple implications");
+        assert_eq!(
+            err.downcast_ref::<RuleError>(),
+            Some(&RuleError::MultipleImplications("A=>B<=>C".to_string()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_missing_bracket() {
+        let result = "[10A=>B".parse::<Rule>();
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Missing closing bracket in rule priority: '[10A=>B'"
+        );
+    }
+
+    #[test]
+    fn error_invalid_priority() {
+        let result = "[high]A=>B".parse::<Rule>();
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid rule priority: 'high'"
+        );
+    }
+
+    #[test]
+    fn error_invalid_label() {
+        let result = "r-1:A=>B".parse::<Rule>();
+        assert_eq!(result.unwrap_err().to_string(), "Invalid rule label: 'r-1'");
+        let result = ":A=>B".parse::<Rule>();
+        assert_eq!(result.unwrap_err().to_string(), "Invalid rule label: ''");
+    }
+}
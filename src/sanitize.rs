@@ -1,20 +1,100 @@
-use std::borrow::Borrow;
+use anyhow::{anyhow, Result};
+use std::{borrow::Borrow, env};
 
-fn remove_spaces(line: impl Borrow<str>) -> String {
+// Replaces every `${VAR}` in the line with the value of the environment variable, `$$` is replaced
+// with a literal `$`. An unset variable is an error unless `allow_unset` is set, in which case it
+// expands to nothing.
+pub fn expand_env(line: impl Borrow<str>, allow_unset: bool) -> Result<String> {
+    let mut result = String::new();
+    let mut chars = line.borrow().chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('$', Some('$')) => {
+                chars.next();
+                result.push('$');
+            }
+            ('$', Some('{')) => {
+                chars.next();
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(anyhow!("Unterminated variable: '${{{}'", name)),
+                    }
+                }
+                match env::var(&name) {
+                    Ok(value) => result.push_str(&value),
+                    Err(_) if allow_unset => {}
+                    Err(_) => return Err(anyhow!("Environment variable is not set: '{}'", name)),
+                }
+            }
+            _ => result.push(c),
+        }
+    }
+    Ok(result)
+}
+
+// Removes all whitespace, for the facts, queries and directives, which don't hold spaces of their
+// own.
+pub fn remove_spaces(line: impl Borrow<str>) -> String {
     line.borrow().split_whitespace().collect()
 }
 
-fn remove_comment(line: impl Borrow<str>) -> String {
-    line.borrow().split_terminator('#').take(1).collect()
+// Removes everything from the first `#` on. The `#` right after the `?` of a table query, e.g.
+// `?#A+B`, doesn't start a comment.
+pub fn remove_comment(line: impl Borrow<str>) -> String {
+    let line = line.borrow();
+    let start = match line.trim_start().strip_prefix("?#") {
+        Some(rest) => line.len() - rest.len(),
+        None => 0,
+    };
+    match line[start..].find('#') {
+        Some(i) => line[..start + i].to_string(),
+        None => line.to_string(),
+    }
+}
+
+// Removes the comment of a single line, every `\r` in it and its leading and trailing whitespace.
+// Interior spaces are kept, the tokenizer skips them.
+pub fn sanitize_line(line: impl Borrow<str>) -> String {
+    remove_comment(line)
+        .replace('\r', "")
+        .trim_matches(|c: char| c.is_ascii_whitespace())
+        .to_string()
+}
+
+// Returns the sanitized line without its trailing `\` when it continues onto the next line. Only
+// rules can be continued, not the facts, the queries or a directive.
+pub fn strip_continuation(line: &str) -> Result<Option<&str>> {
+    match line.strip_suffix('\\') {
+        Some(_)
+            if line.starts_with('=') && !line.starts_with("=>") || line.starts_with(['?', '%']) =>
+        {
+            Err(anyhow!(
+                "Line continuation is only allowed in rules: '{}'",
+                line
+            ))
+        }
+        continued => Ok(continued),
+    }
 }
 
-pub fn sanitize_lines(lines: &[impl Borrow<str>]) -> Vec<String> {
+// Sanitizes every line, see sanitize_line: a `\r` left by CRLF line endings anywhere in the line,
+// leading tabs and trailing spaces are removed, everything else is left to the tokenizer and the
+// parsers of the sections. A rule ending in `\` is joined with the next line. Runs of empty lines
+// are merged into a single empty line and empty lines at the start and end are dropped.
+pub fn sanitize_lines(lines: &[impl Borrow<str>]) -> Result<Vec<String>> {
     let mut result: Vec<String> = Vec::new();
     let mut prev = String::new();
+    let mut continued: Option<String> = None;
     for line in lines.iter() {
-        let mut sanitized = line.borrow().to_string();
-        sanitized = remove_comment(sanitized);
-        sanitized = remove_spaces(sanitized);
+        let mut sanitized = continued.take().unwrap_or_default();
+        sanitized.push_str(&sanitize_line(line.borrow()));
+        if let Some(rest) = strip_continuation(&sanitized)? {
+            continued = Some(rest.to_string());
+            continue;
+        }
         if !result.is_empty() && prev.is_empty() && !sanitized.is_empty() {
             result.push(String::new());
         }
@@ -23,8 +103,67 @@ pub fn sanitize_lines(lines: &[impl Borrow<str>]) -> Vec<String> {
         }
         prev = sanitized
     }
+    if let Some(line) = continued {
+        return Err(anyhow!("Line continuation at end of input: '{}\\'", line));
+    }
 
-    result
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests_expand_env {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn defined() -> Result<()> {
+        env::set_var("EXPERT_SYSTEM_TEST_DEFINED", "AB");
+        assert_eq!(
+            expand_env("=${EXPERT_SYSTEM_TEST_DEFINED}C", false)?,
+            "=ABC"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_variables() -> Result<()> {
+        assert_eq!(expand_env("A + B => C", false)?, "A + B => C");
+        Ok(())
+    }
+
+    #[test]
+    fn literal_dollar() -> Result<()> {
+        assert_eq!(expand_env("$${NOT_A_VAR} $", false)?, "${NOT_A_VAR} $");
+        Ok(())
+    }
+
+    #[test]
+    fn allow_unset() -> Result<()> {
+        assert_eq!(expand_env("=A${EXPERT_SYSTEM_TEST_UNSET}", true)?, "=A");
+        Ok(())
+    }
+
+    #[test]
+    fn error_undefined() {
+        let result = expand_env("=${EXPERT_SYSTEM_TEST_UNSET}", false);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Environment variable is not set: 'EXPERT_SYSTEM_TEST_UNSET'"
+        );
+    }
+
+    #[test]
+    fn error_unterminated() {
+        let result = expand_env("=${FACTS", false);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unterminated variable: '${FACTS'"
+        );
+    }
 }
 
 #[cfg(test)]
@@ -81,6 +220,12 @@ mod tests_remove_comment {
         let result: String = remove_comment(input);
         assert_eq!(result, "hello ");
     }
+
+    #[test]
+    fn table_query() {
+        assert_eq!(remove_comment("  ?#A+B # table"), "  ?#A+B ");
+        assert_eq!(remove_comment("? #A+B"), "? ");
+    }
 }
 
 #[cfg(test)]
@@ -93,31 +238,60 @@ mod tests_sanitize_lines {
     use pretty_assertions::assert_eq;
 
     #[test]
-    fn empty_lines() {
+    fn empty_lines() -> Result<()> {
         let input: Vec<&str> = vec!["  ", "", "\t"];
-        let result: Vec<String> = sanitize_lines(&input);
+        let result: Vec<String> = sanitize_lines(&input)?;
         assert_eq!(result, Vec::<String>::new());
+        Ok(())
     }
 
     #[test]
-    fn empty_lines_at_end() {
+    fn empty_lines_at_end() -> Result<()> {
         let input: Vec<&str> = vec!["hello", "  ", "", "\t"];
-        let result: Vec<String> = sanitize_lines(&input);
+        let result: Vec<String> = sanitize_lines(&input)?;
         assert_eq!(result, vec!["hello"]);
+        Ok(())
     }
 
     #[test]
-    fn merge_empty_lines() {
+    fn merge_empty_lines() -> Result<()> {
         let input: Vec<&str> = vec!["hello", "  ", "", "\t", "world"];
-        let result: Vec<String> = sanitize_lines(&input);
+        let result: Vec<String> = sanitize_lines(&input)?;
         assert_eq!(result, vec!["hello", "", "world"]);
+        Ok(())
+    }
+
+    #[test]
+    fn carriage_returns() -> Result<()> {
+        let input: Vec<&str> = vec!["A => B\r", "A\r+ B => C", "\r"];
+        let result: Vec<String> = sanitize_lines(&input)?;
+        assert_eq!(result, vec!["A => B", "A+ B => C"]);
+        Ok(())
     }
 
     #[test]
-    fn every_other_empty() {
+    fn leading_and_trailing_whitespace() -> Result<()> {
+        let input: Vec<&str> = vec!["\t\tA => B", "=A   ", " \t?B \t "];
+        let result: Vec<String> = sanitize_lines(&input)?;
+        assert_eq!(result, vec!["A => B", "=A", "?B"]);
+        Ok(())
+    }
+
+    #[test]
+    fn interior_spaces() -> Result<()> {
+        // A split implicator stays split, so the tokenizer rejects it.
+        let input: Vec<&str> = vec![" A = > B ", "= A B", "? C"];
+        let result: Vec<String> = sanitize_lines(&input)?;
+        assert_eq!(result, vec!["A = > B", "= A B", "? C"]);
+        Ok(())
+    }
+
+    #[test]
+    fn every_other_empty() -> Result<()> {
         let input: Vec<&str> = vec!["f", "", "o", "", "o"];
-        let result: Vec<String> = sanitize_lines(&input);
+        let result: Vec<String> = sanitize_lines(&input)?;
         assert_eq!(result, vec!["f", "", "o", "", "o"]);
+        Ok(())
     }
 
     #[test]
@@ -128,8 +302,37 @@ mod tests_sanitize_lines {
         let expect_file = test_utils::input_file_path("sanitize_lines/example_input_expected.txt");
         let expected: Vec<String> = read_file(&expect_file)?;
 
-        let result: Vec<String> = sanitize_lines(&input);
+        let result: Vec<String> = sanitize_lines(&input)?;
         assert_eq!(result, expected);
         Ok(())
     }
+
+    #[test]
+    fn continuation() -> Result<()> {
+        let input: Vec<&str> = vec!["A + B \\", "  | C => D", "=A", "?D"];
+        let result: Vec<String> = sanitize_lines(&input)?;
+        assert_eq!(result, vec!["A + B | C => D", "=A", "?D"]);
+        assert_eq!(result, sanitize_lines(&["A + B | C => D", "=A", "?D"])?);
+        let input: Vec<&str> = vec!["A \\ # first", "+ B \\", "=> C"];
+        assert_eq!(sanitize_lines(&input)?, vec!["A + B => C"]);
+        Ok(())
+    }
+
+    #[test]
+    fn error_continuation() {
+        let result = sanitize_lines(&["A + B \\", "=> C \\"]);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Line continuation at end of input: 'A + B => C \\'"
+        );
+        for line in ["=A\\", "?Z\\", "%default false\\"] {
+            let result = sanitize_lines(&[line, "B"]);
+            assert!(result.is_err());
+            assert!(result
+                .unwrap_err()
+                .to_string()
+                .starts_with("Line continuation is only allowed in rules"));
+        }
+    }
 }
@@ -0,0 +1,2254 @@
+use crate::*;
+use cancel::CancelFlag;
+use lint::{Warning, WarningKind};
+use node::Node;
+use operator::OperatorSet;
+use rule::Rule;
+use state::State;
+use truth::Truth;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+// Origin records what determined the value of a symbol.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Origin {
+    Fact,
+    Rule(usize),
+    ClosedWorld,
+}
+
+// Contradiction is reported when two sources disagree on the value of a symbol. `first` sets the
+// symbol to `value`, `second` sets it to the opposite. Initial facts are set before anything else,
+// so when a fact is involved it is always `first`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contradiction {
+    pub symbol: char,
+    pub value: bool,
+    pub first: String,
+    pub second: String,
+}
+
+impl Contradiction {
+    // Returns whether the contradiction is with an initial fact rather than between two rules.
+    pub fn is_fact(&self) -> bool {
+        self.first == Justification::Fact.to_string()
+    }
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_fact() {
+            write!(
+                f,
+                "Contradiction with initial fact '{}{}': {} makes it {}",
+                if self.value { "" } else { "!" },
+                self.symbol,
+                self.second,
+                !self.value
+            )
+        } else {
+            write!(
+                f,
+                "Contradiction for '{}': {} makes it {} but {} makes it {}",
+                self.symbol, self.first, self.value, self.second, !self.value
+            )
+        }
+    }
+}
+
+impl std::error::Error for Contradiction {}
+
+// Justification holds the reason a symbol has its value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Justification {
+    Fact,
+    ClosedWorld,
+    Rule(String),
+}
+
+impl fmt::Display for Justification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Justification::Fact => write!(f, "initial fact"),
+            Justification::ClosedWorld => write!(f, "closed-world assumption"),
+            Justification::Rule(rule) => write!(f, "rule '{}'", rule),
+        }
+    }
+}
+
+impl Justification {
+    // Describes the justification as the clause following an answer, e.g. `via A+B=>Z`.
+    pub fn clause(&self) -> String {
+        match self {
+            Justification::Rule(rule) => format!("via {}", rule),
+            justification => justification.to_string(),
+        }
+    }
+}
+
+// ProofNode is a node in the proof tree of a symbol. A symbol derived by a rule has the proofs of
+// the symbols the rule depended on as children, initial facts and assumptions are leaves.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofNode {
+    pub symbol: char,
+    pub value: bool,
+    pub justification: Justification,
+    pub children: Vec<ProofNode>,
+}
+
+impl ProofNode {
+    fn fmt_indented(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{}{} is {} ({})",
+            "  ".repeat(depth),
+            self.symbol,
+            self.value,
+            self.justification
+        )?;
+        for child in self.children.iter() {
+            child.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ProofNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+// ContradictionTrace holds the derivations of both values of a contradicting symbol, down to the
+// initial facts and assumptions they depend on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContradictionTrace {
+    pub contradiction: Contradiction,
+    pub proofs: [ProofNode; 2],
+}
+
+impl fmt::Display for ContradictionTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.contradiction)?;
+        for proof in self.proofs.iter() {
+            proof.fmt_indented(f, 1)?;
+        }
+        Ok(())
+    }
+}
+
+// Upper bound on the number of assignments evaluated by satisfiable.
+const MAX_SATISFIABLE_RUNS: usize = 1 << 16;
+
+// Checks whether the node can evaluate to true by assigning the free symbols. All other symbols
+// of the node are expected to be present in the state. A node with too many free symbols to
+// search is assumed to be able to hold.
+fn satisfiable(node: &Node, mut state: State, free: &[char]) -> bool {
+    let runs = match permutation_iter::permutation_count(free.len()) {
+        Ok(runs) if runs <= MAX_SATISFIABLE_RUNS => runs,
+        _ => return true,
+    };
+    for permutation in 0..runs {
+        for (i, c) in free.iter().enumerate() {
+            state.set(*c, permutation & (1 << i) != 0);
+        }
+        if node.eval(&state).unwrap_or(false) {
+            return true;
+        }
+    }
+    false
+}
+
+// OrPolicy selects what a fired rule assumes when its conclusion doesn't decide a symbol, e.g. for
+// `A => B | C`. Undetermined assumes nothing, First assumes the first undecided symbol of the
+// conclusion true and All assumes every undecided symbol true, as long as the conclusion can still
+// hold.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum OrPolicy {
+    #[default]
+    Undetermined,
+    First,
+    All,
+}
+
+impl FromStr for OrPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "undetermined" => Ok(OrPolicy::Undetermined),
+            "first" => Ok(OrPolicy::First),
+            "all" => Ok(OrPolicy::All),
+            _ => Err(anyhow!("Invalid or policy: '{}'", s)),
+        }
+    }
+}
+
+// Solver resolves the symbols of a rule set by forward chaining from the initial facts. Rules are
+// fired until no new values can be derived, after which every symbol that can no longer become
+// true is assumed false (closed-world assumption) and the rules are fired again.
+pub struct Solver<'a> {
+    rules: &'a [Rule],
+    symbols: BTreeSet<char>,
+    values: State,
+    origins: HashMap<char, Origin>,
+    supports: HashMap<char, BTreeSet<char>>,
+    ambiguous: BTreeSet<char>,
+    fired: BTreeSet<usize>,
+    default: Truth,
+    stop_on_contradiction: bool,
+    max_depth: Option<usize>,
+    cancel: Option<CancelFlag>,
+    or_policy: OrPolicy,
+    pub warnings: Vec<Warning>,
+    pub contradictions: Vec<Contradiction>,
+    traces: Vec<[ProofNode; 2]>,
+}
+
+// SolvedState holds everything a solver derived, without the rules it borrows, so it can be kept
+// after solving and resumed with the same rules, see Solver::into_state and Solver::resume.
+pub struct SolvedState {
+    symbols: BTreeSet<char>,
+    values: State,
+    origins: HashMap<char, Origin>,
+    supports: HashMap<char, BTreeSet<char>>,
+    ambiguous: BTreeSet<char>,
+    fired: BTreeSet<usize>,
+    default: Truth,
+    stop_on_contradiction: bool,
+    max_depth: Option<usize>,
+    cancel: Option<CancelFlag>,
+    or_policy: OrPolicy,
+    warnings: Vec<Warning>,
+    contradictions: Vec<Contradiction>,
+    traces: Vec<[ProofNode; 2]>,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(rules: &'a [Rule], facts: &str) -> Self {
+        let mut solver = Solver {
+            rules,
+            symbols: rules.iter().flat_map(|r| r.symbols()).collect(),
+            values: State::default(),
+            origins: HashMap::new(),
+            supports: HashMap::new(),
+            ambiguous: BTreeSet::new(),
+            fired: BTreeSet::new(),
+            default: Truth::False,
+            stop_on_contradiction: false,
+            max_depth: None,
+            cancel: None,
+            or_policy: OrPolicy::default(),
+            warnings: Vec::new(),
+            contradictions: Vec::new(),
+            traces: Vec::new(),
+        };
+        for (c, value) in fact_values(facts) {
+            solver.symbols.insert(c);
+            solver.set(c, value, Origin::Fact);
+        }
+        solver
+    }
+
+    // Sets the value of symbols that are neither facts nor derived. By default this is false (the
+    // closed-world assumption), when undetermined no symbol is ever assumed false.
+    pub fn with_default(mut self, default: Truth) -> Self {
+        self.default = default;
+        self
+    }
+
+    // Stops solving at the first contradiction instead of running to the fixpoint, the symbols
+    // that were not derived yet are left undetermined.
+    pub fn with_stop_on_contradiction(mut self, stop: bool) -> Self {
+        self.stop_on_contradiction = stop;
+        self
+    }
+
+    // Limits the number of passes over the rules, the symbols that were not derived within the
+    // limit are left undetermined.
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    // Stops solving as soon as the flag is cancelled, the symbols that were not derived yet are left
+    // undetermined.
+    pub fn with_cancel(mut self, cancel: Option<CancelFlag>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn with_or_policy(mut self, or_policy: OrPolicy) -> Self {
+        self.or_policy = or_policy;
+        self
+    }
+
+    // Returns the state without the rules, to be resumed later with the same rules.
+    pub fn into_state(self) -> SolvedState {
+        SolvedState {
+            symbols: self.symbols,
+            values: self.values,
+            origins: self.origins,
+            supports: self.supports,
+            ambiguous: self.ambiguous,
+            fired: self.fired,
+            default: self.default,
+            stop_on_contradiction: self.stop_on_contradiction,
+            max_depth: self.max_depth,
+            cancel: self.cancel,
+            or_policy: self.or_policy,
+            warnings: self.warnings,
+            contradictions: self.contradictions,
+            traces: self.traces,
+        }
+    }
+
+    // Continues from a state taken by into_state, the rules have to be the ones it was solved
+    // with.
+    pub fn resume(rules: &'a [Rule], state: SolvedState) -> Self {
+        Solver {
+            rules,
+            symbols: state.symbols,
+            values: state.values,
+            origins: state.origins,
+            supports: state.supports,
+            ambiguous: state.ambiguous,
+            fired: state.fired,
+            default: state.default,
+            stop_on_contradiction: state.stop_on_contradiction,
+            max_depth: state.max_depth,
+            cancel: state.cancel,
+            or_policy: state.or_policy,
+            warnings: state.warnings,
+            contradictions: state.contradictions,
+            traces: state.traces,
+        }
+    }
+
+    pub fn cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(|c| c.is_cancelled())
+    }
+
+    pub fn solve(&mut self) {
+        let mut depth = 0;
+        loop {
+            loop {
+                if self.max_depth.is_some_and(|max| depth == max) {
+                    return;
+                }
+                depth += 1;
+                if !self.forward() {
+                    break;
+                }
+            }
+            if self.stopped() || !self.close_world() {
+                break;
+            }
+        }
+    }
+
+    fn stopped(&self) -> bool {
+        self.cancelled() || self.stop_on_contradiction && !self.contradictions.is_empty()
+    }
+
+    pub fn get(&self, symbol: char) -> Truth {
+        match self.values.get(symbol) {
+            Some(b) => Truth::from(b),
+            None if self.symbols.contains(&symbol) => Truth::Undetermined,
+            None => self.default,
+        }
+    }
+
+    // Returns what determined the value of a symbol, or None when the symbol is undetermined.
+    pub fn justification_of(&self, symbol: char) -> Option<Justification> {
+        self.origins.get(&symbol).map(|o| self.justification(*o))
+    }
+
+    // Builds the proof tree of a determined symbol.
+    pub fn proof(&self, symbol: char) -> Option<ProofNode> {
+        self.proof_path(symbol, &mut BTreeSet::new())
+    }
+
+    fn proof_path(&self, symbol: char, path: &mut BTreeSet<char>) -> Option<ProofNode> {
+        let value = self.values.get(symbol)?;
+        path.insert(symbol);
+        let mut children = vec![];
+        for c in self.supports.get(&symbol).into_iter().flatten() {
+            if !path.contains(c) {
+                children.extend(self.proof_path(*c, path));
+            }
+        }
+        path.remove(&symbol);
+        Some(ProofNode {
+            symbol,
+            value,
+            justification: self.justification(self.origins[&symbol]),
+            children,
+        })
+    }
+
+    // Returns the values given by a fact or derived by a rule as a facts line, e.g. `AB!C`, leaving
+    // out the values of the closed-world assumption. Solving again from these facts gives the same
+    // values.
+    pub fn derived_facts(&self) -> String {
+        self.values
+            .iter()
+            .filter(|(c, _)| !matches!(self.origins.get(c), Some(Origin::ClosedWorld)))
+            .map(|(c, value)| format!("{}{}", if value { "" } else { "!" }, c))
+            .collect()
+    }
+
+    // Returns the true symbols derived from the facts, each with the number of rule applications
+    // on the longest path from a fact to it (its BFS level): 0 for a fact, one more than its
+    // deepest support for a derived symbol. Symbols assumed true by closed-world reasoning alone
+    // are left out.
+    pub fn derivation_levels(&self) -> BTreeMap<char, usize> {
+        self.values
+            .iter()
+            .filter(|(_, value)| *value)
+            .filter_map(|(c, _)| Some((c, self.level(c, &mut BTreeSet::new())?)))
+            .collect()
+    }
+
+    fn level(&self, symbol: char, path: &mut BTreeSet<char>) -> Option<usize> {
+        match self.origins.get(&symbol)? {
+            Origin::Fact => Some(0),
+            Origin::ClosedWorld => None,
+            Origin::Rule(_) => {
+                path.insert(symbol);
+                let mut deepest = None;
+                for c in self.supports.get(&symbol).into_iter().flatten() {
+                    if !path.contains(c) {
+                        deepest = deepest.max(self.level(*c, path));
+                    }
+                }
+                path.remove(&symbol);
+                Some(deepest.map_or(1, |level| level + 1))
+            }
+        }
+    }
+
+    // Explains why a symbol is undetermined: no rule concludes it, a rule concluding it has an
+    // undetermined premise, or the rules concluding it allow both values. Returns None when the
+    // symbol is determined.
+    pub fn explain_undetermined(&self, symbol: char) -> Option<String> {
+        if self.get(symbol) != Truth::Undetermined {
+            return None;
+        }
+        let state = self.values;
+        let mut concluding = vec![];
+        for rule in self.rules.iter() {
+            for (premise, conclusion) in rule.implications() {
+                if !conclusion.symbols().contains(&symbol) {
+                    continue;
+                }
+                match premise.eval3(&state) {
+                    Truth::Undetermined => {
+                        let c = premise
+                            .symbols()
+                            .into_iter()
+                            .find(|c| self.get(*c) == Truth::Undetermined)?;
+                        return Some(format!(
+                            "{} is undetermined: rule {} has undetermined premise symbol {}",
+                            symbol, rule, c
+                        ));
+                    }
+                    // A rule whose premise doesn't hold says nothing about the symbol.
+                    Truth::True => concluding.push(rule),
+                    Truth::False => {}
+                }
+            }
+        }
+        Some(match concluding.first() {
+            None => format!("{} is undetermined: no rule concludes it", symbol),
+            Some(rule) => format!(
+                "{} is undetermined: rule {} allows it to be both true and false",
+                symbol, rule
+            ),
+        })
+    }
+
+    // Explains why a symbol is false: it is a false fact, a rule makes it false, or no rule
+    // concluding it fired. In the last case the rule closest to firing is given, the one with the
+    // fewest premise literals that don't hold, along with the first of those. Returns None when
+    // the symbol is not false.
+    pub fn explain_false(&self, symbol: char) -> Option<String> {
+        if self.get(symbol) != Truth::False {
+            return None;
+        }
+        match self.origins.get(&symbol) {
+            Some(Origin::Fact) => return Some(format!("{} is false: it is a false fact", symbol)),
+            Some(Origin::Rule(index)) => {
+                return Some(format!(
+                    "{} is false: rule {} makes it false",
+                    symbol, self.rules[*index]
+                ))
+            }
+            Some(Origin::ClosedWorld) | None => {}
+        }
+        let mut state = self.values;
+        state.set_true(symbol);
+        let mut closest: Option<(usize, &Rule, char)> = None;
+        for rule in self.rules.iter() {
+            for (premise, conclusion) in rule.implications() {
+                // Only rules that can make the symbol true count.
+                if !conclusion.symbols().contains(&symbol)
+                    || conclusion.eval3(&state) == Truth::False
+                {
+                    continue;
+                }
+                let unmet: Vec<char> = premise
+                    .literals()
+                    .iter()
+                    .filter(|l| self.get(l.symbol) != Truth::from(!l.negated))
+                    .map(|l| l.symbol)
+                    .collect();
+                if let Some(c) = unmet.first() {
+                    if closest.is_none_or(|(count, _, _)| unmet.len() < count) {
+                        closest = Some((unmet.len(), rule, *c));
+                    }
+                }
+            }
+        }
+        Some(match closest {
+            None => format!("{} is false: no rule concludes it", symbol),
+            Some((_, rule, c)) => format!(
+                "{} is false: no rule concluding it fired, closest is rule {} with unmet premise symbol {}",
+                symbol, rule, c
+            ),
+        })
+    }
+
+    // Returns the rules of which no premise has been true so far, a biconditional rule fires when
+    // either side is true.
+    pub fn unfired_rules(&self) -> Vec<String> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(index, rule)| rule.enabled && !self.fired.contains(index))
+            .map(|(_, rule)| rule.to_string())
+            .collect()
+    }
+
+    // Returns the contradictions found so far, each with the derivations of both values.
+    pub fn traces(&self) -> Vec<ContradictionTrace> {
+        self.contradictions
+            .iter()
+            .zip(self.traces.iter())
+            .map(|(contradiction, proofs)| ContradictionTrace {
+                contradiction: contradiction.clone(),
+                proofs: proofs.clone(),
+            })
+            .collect()
+    }
+
+    // Returns the values of all determined symbols.
+    pub fn state(&self) -> HashMap<char, Truth> {
+        self.values.to_map()
+    }
+
+    // Fires every rule of which the premise holds, returns whether any value changed.
+    fn forward(&mut self) -> bool {
+        let rules = self.rules;
+        let mut changed = false;
+        for (index, rule) in rules.iter().enumerate() {
+            for (premise, conclusion) in rule.implications() {
+                if self.stopped() {
+                    return false;
+                }
+                if premise.eval3(&self.values) == Truth::True {
+                    self.fired.insert(index);
+                    changed |= self.assert_conclusion(index, premise, conclusion);
+                }
+            }
+        }
+        changed
+    }
+
+    fn assert_conclusion(&mut self, index: usize, premise: &Node, conclusion: &Node) -> bool {
+        let mut changed = false;
+        let mut undecided = false;
+        for symbol in conclusion.symbols() {
+            let value = match self.possible_values(conclusion, symbol) {
+                [false, false] => {
+                    let symbols: Vec<char> = conclusion.symbols().into_iter().collect();
+                    if !satisfiable(conclusion, State::default(), &symbols) {
+                        let rule = self.describe(Origin::Rule(index));
+                        let proofs = [
+                            self.derivation(symbol, true, Origin::Rule(index)),
+                            self.derivation(symbol, false, Origin::Rule(index)),
+                        ];
+                        self.contradict(
+                            Contradiction {
+                                symbol,
+                                value: true,
+                                first: rule.clone(),
+                                second: rule,
+                            },
+                            proofs,
+                        );
+                    }
+                    continue;
+                }
+                [true, false] => false,
+                [false, true] => true,
+                [true, true] => {
+                    if !self.values.contains(symbol) {
+                        self.ambiguous.insert(symbol);
+                        undecided = true;
+                    }
+                    continue;
+                }
+            };
+            if self.set(symbol, value, Origin::Rule(index)) {
+                let mut supports = premise.symbols();
+                supports.extend(
+                    conclusion
+                        .symbols()
+                        .into_iter()
+                        .filter(|c| *c != symbol && self.values.contains(*c)),
+                );
+                self.supports.insert(symbol, supports);
+                changed = true;
+            }
+        }
+        if undecided {
+            changed |= self.apply_or_policy(index, premise, conclusion);
+        }
+        changed
+    }
+
+    // Assumes the undecided symbols of the conclusion true according to the or policy, in order of
+    // appearance, returns whether any value changed. A symbol is only assumed true when the
+    // conclusion can still hold, under First a symbol that is already true decides the conclusion.
+    fn apply_or_policy(&mut self, index: usize, premise: &Node, conclusion: &Node) -> bool {
+        let mut changed = false;
+        for symbol in conclusion.symbols_in_order() {
+            match (self.or_policy, self.values.get(symbol)) {
+                (OrPolicy::Undetermined, _) | (OrPolicy::First, Some(true)) => break,
+                (_, Some(_)) => continue,
+                (_, None) if !self.possible_values(conclusion, symbol)[1] => continue,
+                (policy, None) => {
+                    if policy == OrPolicy::First {
+                        self.warn(
+                            WarningKind::UndecidedConclusion,
+                            format!(
+                                "rule '{}' doesn't decide '{}', assuming it true",
+                                self.rules[index], symbol
+                            ),
+                        );
+                    }
+                    self.set(symbol, true, Origin::Rule(index));
+                    self.supports.insert(symbol, premise.symbols());
+                    changed = true;
+                    if policy == OrPolicy::First {
+                        break;
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    // Returns for both values of the symbol whether the node can still be true, given the values of
+    // all other known symbols of the node.
+    fn possible_values(&self, node: &Node, symbol: char) -> [bool; 2] {
+        let free: Vec<char> = node
+            .symbols()
+            .into_iter()
+            .filter(|c| *c != symbol && !self.values.contains(*c))
+            .collect();
+        let mut state = self.values;
+        let mut possible = [false; 2];
+        for value in [false, true] {
+            state.set(symbol, value);
+            possible[value as usize] = satisfiable(node, state, &free);
+        }
+        possible
+    }
+
+    // Assumes false every unknown symbol that can not be derived anymore, returns whether any symbol
+    // was assumed false. A symbol can still be derived when it is ambiguous, or when it is concluded
+    // by a rule of which the premise can hold through other symbols that can still be derived.
+    fn close_world(&mut self) -> bool {
+        if self.default != Truth::False {
+            return false;
+        }
+        let rules = self.rules;
+        let mut possible: BTreeSet<char> = self
+            .ambiguous
+            .iter()
+            .filter(|c| !self.values.contains(**c))
+            .copied()
+            .collect();
+        loop {
+            let mut grew = false;
+            for rule in rules.iter() {
+                for (premise, conclusion) in rule.implications() {
+                    let unknown: Vec<char> = conclusion
+                        .symbols()
+                        .into_iter()
+                        .filter(|c| !self.values.contains(*c) && !possible.contains(c))
+                        .collect();
+                    if !unknown.is_empty() && self.can_hold(premise, &possible) {
+                        possible.extend(unknown);
+                        grew = true;
+                    }
+                }
+            }
+            if !grew {
+                break;
+            }
+        }
+
+        let closed: Vec<char> = self
+            .symbols
+            .iter()
+            .filter(|c| !self.values.contains(**c) && !possible.contains(c))
+            .copied()
+            .collect();
+        for c in closed.iter() {
+            self.set(*c, false, Origin::ClosedWorld);
+        }
+        !closed.is_empty()
+    }
+
+    // Checks whether the premise can hold when all possible symbols are free and all other unknown
+    // symbols are false.
+    fn can_hold(&self, premise: &Node, possible: &BTreeSet<char>) -> bool {
+        let mut state = self.values;
+        let mut free = Vec::new();
+        for c in premise.symbols() {
+            if state.contains(c) {
+                continue;
+            }
+            if possible.contains(&c) {
+                free.push(c);
+            } else {
+                state.set_false(c);
+            }
+        }
+        satisfiable(premise, state, &free)
+    }
+
+    fn set(&mut self, symbol: char, value: bool, origin: Origin) -> bool {
+        match self.values.get(symbol) {
+            Some(v) if v == value => false,
+            Some(_) => self.resolve_conflict(symbol, value, origin),
+            None => {
+                self.values.set(symbol, value);
+                self.origins.insert(symbol, origin);
+                true
+            }
+        }
+    }
+
+    // Resolves two conflicting values of a symbol. When both values are derived by rules with a
+    // different priority, the rule with the highest priority wins. Between rules of the same
+    // priority the more specific rule wins, otherwise it's a contradiction.
+    fn resolve_conflict(&mut self, symbol: char, value: bool, origin: Origin) -> bool {
+        let existing = self.origins[&symbol];
+        match (existing, origin) {
+            (Origin::ClosedWorld, _) => {
+                self.values.set(symbol, value);
+                self.origins.insert(symbol, origin);
+                true
+            }
+            (Origin::Rule(old), Origin::Rule(new)) if self.rank(old) != self.rank(new) => {
+                let (winner, loser) = if self.rank(new) > self.rank(old) {
+                    (new, old)
+                } else {
+                    (old, new)
+                };
+                self.warn(
+                    WarningKind::RuleOverride,
+                    format!(
+                        "rule '{}' overrides rule '{}' for '{}'",
+                        self.rules[winner], self.rules[loser], symbol
+                    ),
+                );
+                if winner == new {
+                    self.values.set(symbol, value);
+                    self.origins.insert(symbol, origin);
+                }
+                winner == new
+            }
+            _ => {
+                let proofs = [
+                    self.derivation(symbol, !value, existing),
+                    self.derivation(symbol, value, origin),
+                ];
+                self.contradict(
+                    Contradiction {
+                        symbol,
+                        value: !value,
+                        first: self.describe(existing),
+                        second: self.describe(origin),
+                    },
+                    proofs,
+                );
+                false
+            }
+        }
+    }
+
+    // Returns what decides between conflicting rules: the priority, then the specificity.
+    fn rank(&self, index: usize) -> (i32, usize) {
+        (self.rules[index].priority, self.rules[index].specificity())
+    }
+
+    fn justification(&self, origin: Origin) -> Justification {
+        match origin {
+            Origin::Fact => Justification::Fact,
+            Origin::Rule(index) => Justification::Rule(self.rules[index].to_string()),
+            Origin::ClosedWorld => Justification::ClosedWorld,
+        }
+    }
+
+    fn describe(&self, origin: Origin) -> String {
+        self.justification(origin).to_string()
+    }
+
+    fn warn(&mut self, kind: WarningKind, message: String) {
+        let warning = Warning::new(kind, message);
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    fn contradict(&mut self, contradiction: Contradiction, proofs: [ProofNode; 2]) {
+        if !self.contradictions.contains(&contradiction) {
+            self.contradictions.push(contradiction);
+            self.traces.push(proofs);
+        }
+    }
+
+    // Builds the proof tree of the symbol having the value because of the origin, which may differ
+    // from the value the symbol has. A rule depends on all its other determined symbols.
+    fn derivation(&self, symbol: char, value: bool, origin: Origin) -> ProofNode {
+        let mut children = vec![];
+        if let Origin::Rule(index) = origin {
+            let mut path = BTreeSet::from([symbol]);
+            for c in self.rules[index].symbols() {
+                if c != symbol {
+                    children.extend(self.proof_path(c, &mut path));
+                }
+            }
+        }
+        ProofNode {
+            symbol,
+            value,
+            justification: self.justification(origin),
+            children,
+        }
+    }
+}
+
+// Query is a single queried symbol, a negated query asks whether the symbol is false. An open
+// query, e.g. `~Z`, is resolved without the closed-world assumption, so a symbol that can't be
+// derived is undetermined instead of false. A query with assumptions, e.g. `Z|A,!B`, is resolved
+// with the assumptions layered over the initial facts. It is serialized as written, e.g. `!Z|A`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Query {
+    pub symbol: char,
+    pub negated: bool,
+    pub open: bool,
+    pub assumptions: State,
+}
+
+impl Query {
+    // Splits a validated query line, e.g. `A!B~CZ|A,!B`, into its queries.
+    pub fn parse_all(queries: &str) -> Vec<Query> {
+        let mut result: Vec<Query> = vec![];
+        let mut negated = false;
+        let mut open = false;
+        let mut assuming = false;
+        for c in queries.chars() {
+            match c {
+                '!' => negated = true,
+                '~' => open = true,
+                '|' | ',' => assuming = true,
+                symbol if assuming => {
+                    if let Some(query) = result.last_mut() {
+                        query.assumptions.set(symbol, !negated);
+                    }
+                    negated = false;
+                    assuming = false;
+                }
+                symbol => {
+                    result.push(Query {
+                        symbol,
+                        negated,
+                        open,
+                        assumptions: State::default(),
+                    });
+                    negated = false;
+                    open = false;
+                }
+            }
+        }
+        result
+    }
+}
+
+impl From<char> for Query {
+    fn from(symbol: char) -> Self {
+        Query {
+            symbol,
+            negated: false,
+            open: false,
+            assumptions: State::default(),
+        }
+    }
+}
+
+// Parses a single query, e.g. `~!Z|A,!B`, whitespace is ignored.
+impl FromStr for Query {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let query: String = s.split_whitespace().collect();
+        match input::split_queries(&query, OperatorSet::default())?[..] {
+            [query] => Ok(Query::parse_all(query)[0]),
+            _ => Err(anyhow!("Expected a single query: '{}'", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for Query {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Query> for String {
+    fn from(query: Query) -> Self {
+        query.to_string()
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.open {
+            write!(f, "~")?;
+        }
+        if self.negated {
+            write!(f, "!")?;
+        }
+        write!(f, "{}", self.symbol)?;
+        if !self.assumptions.is_empty() {
+            let assumptions: Vec<String> = self
+                .assumptions
+                .iter()
+                .map(|(c, value)| format!("{}{}", if value { "" } else { "!" }, c))
+                .collect();
+            write!(f, " | {}", assumptions.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+// RunOutcome holds the answers to the queries along with everything noteworthy that happened
+// while solving.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunOutcome {
+    pub answers: Vec<(Query, Truth)>,
+    pub justifications: HashMap<Query, Justification>,
+    pub proofs: HashMap<Query, ProofNode>,
+    pub explanations: HashMap<Query, String>,
+    pub warnings: Vec<Warning>,
+    pub contradictions: Vec<Contradiction>,
+    pub traces: Vec<ContradictionTrace>,
+    pub unfired_rules: Vec<String>,
+    pub derived_facts: String,
+    pub cancelled: bool,
+}
+
+// Mode selects the rules a run solves with. Forward chains over all rules, while Backward only uses
+// the rules that can contribute to the queries.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Forward,
+    Backward,
+}
+
+impl FromStr for Mode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "forward" => Ok(Mode::Forward),
+            "backward" => Ok(Mode::Backward),
+            _ => Err(anyhow!("Invalid mode: '{}'", s)),
+        }
+    }
+}
+
+// SolveConfig holds the settings of a run, it is constructed through SolveConfig::builder().
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveConfig {
+    pub mode: Mode,
+    pub max_depth: Option<usize>,
+    pub default: Truth,
+    pub stop_on_contradiction: bool,
+    pub cancel: Option<CancelFlag>,
+    pub or_policy: OrPolicy,
+}
+
+impl Default for SolveConfig {
+    fn default() -> Self {
+        SolveConfig {
+            mode: Mode::Forward,
+            max_depth: None,
+            default: Truth::False,
+            stop_on_contradiction: false,
+            cancel: None,
+            or_policy: OrPolicy::Undetermined,
+        }
+    }
+}
+
+impl SolveConfig {
+    pub fn builder() -> SolveConfigBuilder {
+        SolveConfigBuilder::default()
+    }
+}
+
+#[derive(Default)]
+pub struct SolveConfigBuilder {
+    config: SolveConfig,
+}
+
+impl SolveConfigBuilder {
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.config.mode = mode;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.config.max_depth = Some(max_depth);
+        self
+    }
+
+    pub fn default_value(mut self, default: Truth) -> Self {
+        self.config.default = default;
+        self
+    }
+
+    pub fn stop_on_contradiction(mut self, stop: bool) -> Self {
+        self.config.stop_on_contradiction = stop;
+        self
+    }
+
+    pub fn cancel(mut self, cancel: CancelFlag) -> Self {
+        self.config.cancel = Some(cancel);
+        self
+    }
+
+    pub fn or_policy(mut self, or_policy: OrPolicy) -> Self {
+        self.config.or_policy = or_policy;
+        self
+    }
+
+    pub fn build(self) -> SolveConfig {
+        self.config
+    }
+}
+
+pub fn run(rules: &[Rule], facts: &str, queries: &str, config: &SolveConfig) -> RunOutcome {
+    let relevant: Vec<Rule>;
+    let rules = match config.mode {
+        Mode::Forward => rules,
+        Mode::Backward => {
+            relevant = rule::relevant_rules(rules, queries)
+                .into_iter()
+                .cloned()
+                .collect();
+            &relevant
+        }
+    };
+    let solve = |assumptions: &State, default| {
+        let mut solver = Solver::new(rules, &assume(facts, assumptions))
+            .with_default(default)
+            .with_max_depth(config.max_depth)
+            .with_stop_on_contradiction(config.stop_on_contradiction)
+            .with_cancel(config.cancel.clone())
+            .with_or_policy(config.or_policy);
+        solver.solve();
+        solver
+    };
+    let solver = solve(&State::default(), config.default);
+    let queries = Query::parse_all(queries);
+    // Open queries are answered by a solver that never assumes a symbol false, and queries with
+    // assumptions by a solver of their own. Queries sharing both are answered by the same solver.
+    let mut solvers = HashMap::new();
+    for q in queries
+        .iter()
+        .filter(|q| q.open || !q.assumptions.is_empty())
+    {
+        solvers.entry((q.assumptions, q.open)).or_insert_with(|| {
+            let default = if q.open {
+                Truth::Undetermined
+            } else {
+                config.default
+            };
+            solve(&q.assumptions, default)
+        });
+    }
+    let solver_for = |q: &Query| solvers.get(&(q.assumptions, q.open)).unwrap_or(&solver);
+    RunOutcome {
+        justifications: queries
+            .iter()
+            .filter_map(|q| Some((*q, solver_for(q).justification_of(q.symbol)?)))
+            .collect(),
+        proofs: queries
+            .iter()
+            .filter_map(|q| Some((*q, solver_for(q).proof(q.symbol)?)))
+            .collect(),
+        explanations: queries
+            .iter()
+            .filter_map(|q| Some((*q, solver_for(q).explain_false(q.symbol)?)))
+            .collect(),
+        answers: queries
+            .iter()
+            .map(|q| {
+                let truth = solver_for(q).get(q.symbol);
+                (*q, if q.negated { !truth } else { truth })
+            })
+            .collect(),
+        traces: solver.traces(),
+        unfired_rules: solver.unfired_rules(),
+        derived_facts: solver.derived_facts(),
+        cancelled: solver.cancelled(),
+        warnings: solver.warnings,
+        contradictions: solver.contradictions,
+    }
+}
+
+// Returns the proof tree of the query, or None when the query is undetermined.
+pub fn prove(rules: &[Rule], facts: &str, query: char) -> Option<ProofNode> {
+    let mut solver = Solver::new(rules, facts);
+    solver.solve();
+    solver.proof(query)
+}
+
+// Solves the rules and returns the BFS level of every true symbol derived from the facts, see
+// Solver::derivation_levels.
+pub fn closure(rules: &[Rule], facts: &str) -> BTreeMap<char, usize> {
+    let mut solver = Solver::new(rules, facts);
+    solver.solve();
+    solver.derivation_levels()
+}
+
+// Explains why the query is undetermined, or returns None when it is determined.
+pub fn explain_undetermined(
+    rules: &[Rule],
+    facts: &str,
+    default: Truth,
+    query: char,
+) -> Option<String> {
+    let mut solver = Solver::new(rules, facts).with_default(default);
+    solver.solve();
+    solver.explain_undetermined(query)
+}
+
+// Solves the rules and returns a trace for every contradiction found.
+pub fn explain_contradictions(rules: &[Rule], facts: &str) -> Vec<ContradictionTrace> {
+    let mut solver = Solver::new(rules, facts);
+    solver.solve();
+    solver.traces()
+}
+
+// Upper bound on the number of fact subsets evaluated by all_supports.
+const MAX_SUPPORT_RUNS: usize = 1 << 12;
+
+// Advances the indices to the next combination of `indices.len()` out of `n` indices in
+// lexicographic order, returns false when there is no next combination.
+fn next_combination(indices: &mut [usize], n: usize) -> bool {
+    let k = indices.len();
+    for i in (0..k).rev() {
+        if indices[i] < n - k + i {
+            indices[i] += 1;
+            for j in i + 1..k {
+                indices[j] = indices[j - 1] + 1;
+            }
+            return true;
+        }
+    }
+    false
+}
+
+// Returns every minimal subset of the candidates for which proves holds, subsets are tried from
+// small to large. The search gives up after MAX_SUPPORT_RUNS subsets.
+fn minimal_subsets(
+    candidates: &[char],
+    mut proves: impl FnMut(&BTreeSet<char>) -> bool,
+) -> Vec<BTreeSet<char>> {
+    let mut subsets: Vec<BTreeSet<char>> = vec![];
+    let mut runs = 0;
+    for size in 0..=candidates.len() {
+        let mut indices: Vec<usize> = (0..size).collect();
+        loop {
+            let subset: BTreeSet<char> = indices.iter().map(|i| candidates[*i]).collect();
+            if !subsets.iter().any(|s| s.is_subset(&subset)) {
+                if runs == MAX_SUPPORT_RUNS {
+                    return subsets;
+                }
+                runs += 1;
+                if proves(&subset) {
+                    subsets.push(subset);
+                }
+            }
+            if !next_combination(&mut indices, candidates.len()) {
+                break;
+            }
+        }
+    }
+    subsets
+}
+
+// Returns the symbols of the rules that can contribute to the query, including the query itself.
+fn relevant_symbols(rules: &[Rule], query: char) -> BTreeSet<char> {
+    let mut relevant: BTreeSet<char> = rule::relevant_rules(rules, &query.to_string())
+        .iter()
+        .flat_map(|r| r.symbols())
+        .collect();
+    relevant.insert(query);
+    relevant
+}
+
+// Splits a validated facts line, e.g. `A!B`, into its symbols and their values.
+// Returns the facts with the assumptions layered over them, an assumption replaces a fact on the
+// same symbol.
+fn assume(facts: &str, assumptions: &State) -> String {
+    fact_values(facts)
+        .into_iter()
+        .filter(|(c, _)| !assumptions.contains(*c))
+        .chain(assumptions.iter())
+        .map(|(c, value)| format!("{}{}", if value { "" } else { "!" }, c))
+        .collect()
+}
+
+fn fact_values(facts: &str) -> Vec<(char, bool)> {
+    Query::parse_all(facts)
+        .into_iter()
+        .map(|q| (q.symbol, !q.negated))
+        .collect()
+}
+
+// Returns every minimal set of initial facts that proves the query on its own. Only true facts
+// relevant to the query are considered, false facts are part of every set that is tried.
+pub fn all_supports(rules: &[Rule], facts: &str, query: char) -> Vec<BTreeSet<char>> {
+    let relevant = relevant_symbols(rules, query);
+    let (trues, falses): (Vec<_>, Vec<_>) = fact_values(facts).into_iter().partition(|f| f.1);
+    let falses: String = falses.iter().map(|(c, _)| format!("!{}", c)).collect();
+    let mut candidates: Vec<char> = trues
+        .into_iter()
+        .map(|(c, _)| c)
+        .filter(|c| relevant.contains(c))
+        .collect();
+    candidates.sort_unstable();
+    candidates.dedup();
+    minimal_subsets(&candidates, |subset| {
+        let assumed: String = falses.chars().chain(subset.iter().copied()).collect();
+        let mut solver = Solver::new(rules, &assumed);
+        solver.solve();
+        solver.get(query) == Truth::True
+    })
+}
+
+// Returns every minimal set of additional facts that, together with the initial facts, proves the
+// query without contradictions. Only symbols that are relevant to the query, not concluded by any
+// rule and not true already are assumed. An empty set means the initial facts prove the query.
+pub fn abduce(rules: &[Rule], facts: &str, query: char) -> Vec<BTreeSet<char>> {
+    let mut solver = Solver::new(rules, facts);
+    solver.solve();
+    let concluded: BTreeSet<char> = rules
+        .iter()
+        .flat_map(|r| r.implications())
+        .flat_map(|(_, conclusion)| conclusion.symbols())
+        .collect();
+    let candidates: Vec<char> = relevant_symbols(rules, query)
+        .into_iter()
+        .filter(|c| !concluded.contains(c) && solver.get(*c) != Truth::True)
+        .collect();
+    minimal_subsets(&candidates, |subset| {
+        let assumed: String = facts.chars().chain(subset.iter().copied()).collect();
+        let mut solver = Solver::new(rules, &assumed);
+        solver.solve();
+        solver.get(query) == Truth::True && solver.contradictions.is_empty()
+    })
+}
+
+#[cfg(test)]
+mod tests_solver {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    // Counts the allocations made on every thread, so a test can check that a piece of code
+    // doesn't allocate without other tests running in parallel interfering.
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations() -> usize {
+        ALLOCATIONS.with(|count| count.get())
+    }
+
+    #[test]
+    fn forward_without_allocating() -> Result<()> {
+        // None of the premises hold, so a pass only evaluates them against the state.
+        let rules = test_utils::rules(&["A+B=>C", "C|!D=>E", "E<=>F", "!A=>G"])?;
+        let mut solver = Solver::new(&rules, "A");
+        let before = allocations();
+        let changed = solver.forward();
+        assert_eq!(allocations() - before, 0);
+        assert!(!changed);
+        Ok(())
+    }
+
+    #[test]
+    fn satisfiable_search_limit() -> Result<()> {
+        // A + !A never holds, but twenty free symbols are too many to search.
+        let rules = test_utils::rules(&["A+!A=>B"])?;
+        let premise = &rules[0].premise;
+        assert!(!satisfiable(premise, State::default(), &['A']));
+        let free: Vec<char> = ('A'..='T').collect();
+        assert!(satisfiable(premise, State::default(), &free));
+        let free: Vec<char> = (0..symbol_table::MAX_SYMBOLS)
+            .map(symbol_table::internal_symbol)
+            .collect();
+        assert!(satisfiable(premise, State::default(), &free));
+        Ok(())
+    }
+
+    #[test]
+    fn serde_round_trip() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["A=>B", "B=>C", "D=>E", "E=>!C", "F=>G|H"])?,
+            "ADF",
+            "C!E~GZ|F,!A",
+            &SolveConfig::default(),
+        );
+        assert!(!outcome.traces.is_empty());
+        let json = serde_json::to_string(&outcome)?;
+        assert_eq!(serde_json::from_str::<RunOutcome>(&json)?, outcome);
+
+        let proof = prove(&test_utils::rules(&["A=>B"])?, "A", 'B').unwrap();
+        let json = serde_json::to_value(&proof)?;
+        assert_eq!(json["justification"], serde_json::json!({"rule": "A=>B"}));
+        assert_eq!(json["children"][0]["justification"], "fact");
+        assert_eq!(serde_json::from_value::<ProofNode>(json)?, proof);
+        Ok(())
+    }
+
+    #[test]
+    fn query_from_str() -> Result<()> {
+        let query: Query = "~!Z | A, !B".parse()?;
+        assert_eq!(query, Query::parse_all("~!Z|A,!B")[0]);
+        assert_eq!(query.to_string().parse::<Query>()?, query);
+        assert_eq!(serde_json::to_string(&query)?, "\"~!Z | A,!B\"");
+        assert_eq!(
+            "ZY".parse::<Query>().unwrap_err().to_string(),
+            "Expected a single query: 'ZY'"
+        );
+        assert_eq!(
+            "z".parse::<Query>().unwrap_err().to_string(),
+            "Invalid identifier in query: 'z'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn explain_contradiction() -> Result<()> {
+        let rules = test_utils::rules(&["A=>B", "B=>C", "D=>E", "E=>!C"])?;
+        let traces = explain_contradictions(&rules, "AD");
+        assert_eq!(traces.len(), 1);
+        assert_eq!(
+            traces[0].to_string(),
+            indoc! {"
+                Contradiction for 'C': rule 'B=>C' makes it true but rule 'E=>!C' makes it false
+                  C is true (rule 'B=>C')
+                    B is true (rule 'A=>B')
+                      A is true (initial fact)
+                  C is false (rule 'E=>!C')
+                    E is true (rule 'D=>E')
+                      D is true (initial fact)
+            "}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn explain_labelled_contradiction() -> Result<()> {
+        let rules = test_utils::rules(&["r1:A=>B", "r2:A=>!B"])?;
+        let traces = explain_contradictions(&rules, "A");
+        assert_eq!(traces.len(), 1);
+        assert_eq!(
+            traces[0].to_string(),
+            indoc! {"
+                Contradiction for 'B': rule 'r1' makes it true but rule 'r2' makes it false
+                  B is true (rule 'r1')
+                    A is true (initial fact)
+                  B is false (rule 'r2')
+                    A is true (initial fact)
+            "}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn explain_unsatisfiable_conclusion() -> Result<()> {
+        let rules = test_utils::rules(&["A=>B+!B"])?;
+        let traces = explain_contradictions(&rules, "A");
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].proofs[0].children.len(), 1);
+        assert_eq!(traces[0].proofs[1].value, false);
+        Ok(())
+    }
+
+    #[test]
+    fn solve_config() -> Result<()> {
+        let config = SolveConfig::builder()
+            .mode(Mode::Backward)
+            .max_depth(500)
+            .default_value(Truth::Undetermined)
+            .build();
+        assert_eq!(
+            config,
+            SolveConfig {
+                mode: Mode::Backward,
+                max_depth: Some(500),
+                default: Truth::Undetermined,
+                stop_on_contradiction: false,
+                cancel: None,
+                or_policy: OrPolicy::Undetermined,
+            }
+        );
+        let rules = test_utils::rules(&["A=>B", "B=>C", "A=>D", "A=>!D"])?;
+        let outcome = run(&rules, "A", "CE", &config);
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('C'), Truth::True),
+                (Query::from('E'), Truth::Undetermined)
+            ]
+        );
+        assert!(outcome.contradictions.is_empty());
+        assert_eq!(
+            run(&rules, "A", "CE", &SolveConfig::default())
+                .contradictions
+                .len(),
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn why() -> Result<()> {
+        let rules = test_utils::rules(&["A+B=>Z"])?;
+        let outcome = run(&rules, "AB", "ZA", &SolveConfig::default());
+        assert_eq!(
+            outcome.justifications[&Query::from('Z')].clause(),
+            "via A+B=>Z"
+        );
+        assert_eq!(
+            outcome.justifications[&Query::from('A')].clause(),
+            "initial fact"
+        );
+
+        let outcome = run(&rules, "A", "Z", &SolveConfig::default());
+        assert_eq!(
+            outcome.justifications[&Query::from('Z')].clause(),
+            "closed-world assumption"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn explain_false() -> Result<()> {
+        let rules = test_utils::rules(&["A+B=>Z", "C=>Z", "D+!A=>E"])?;
+        let mut solver = Solver::new(&rules, "A!C");
+        solver.solve();
+        assert_eq!(
+            solver.explain_false('Z'),
+            Some(
+                "Z is false: no rule concluding it fired, closest is rule A+B=>Z with unmet \
+                 premise symbol B"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            solver.explain_false('C'),
+            Some("C is false: it is a false fact".to_string())
+        );
+        assert_eq!(
+            solver.explain_false('F'),
+            Some("F is false: no rule concludes it".to_string())
+        );
+        assert_eq!(
+            solver.explain_false('E'),
+            Some(
+                "E is false: no rule concluding it fired, closest is rule D+!A=>E with unmet \
+                 premise symbol D"
+                    .to_string()
+            )
+        );
+        assert_eq!(solver.explain_false('A'), None);
+
+        let outcome = run(&rules, "A", "Z!A", &SolveConfig::default());
+        assert!(outcome.explanations.contains_key(&Query::from('Z')));
+        assert_eq!(outcome.explanations.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn explain_undetermined() -> Result<()> {
+        let rules = test_utils::rules(&["A=>B|C", "A+B=>Z", "D=>E|F"])?;
+        assert_eq!(
+            super::explain_undetermined(&rules, "A", Truth::False, 'Z'),
+            Some("Z is undetermined: rule A+B=>Z has undetermined premise symbol B".to_string())
+        );
+        assert_eq!(
+            super::explain_undetermined(&rules, "A", Truth::False, 'B'),
+            Some("B is undetermined: rule A=>B|C allows it to be both true and false".to_string())
+        );
+        assert_eq!(
+            super::explain_undetermined(&rules, "A", Truth::False, 'A'),
+            None
+        );
+        assert_eq!(
+            super::explain_undetermined(&rules, "A", Truth::False, 'E'),
+            None
+        );
+
+        let mut solver = Solver::new(&rules, "A").with_default(Truth::Undetermined);
+        solver.solve();
+        assert_eq!(
+            solver.explain_undetermined('D'),
+            Some("D is undetermined: no rule concludes it".to_string())
+        );
+
+        let rules = test_utils::rules(&["X=>Z", "A=>Z|B"])?;
+        assert_eq!(
+            super::explain_undetermined(&rules, "A", Truth::False, 'Z'),
+            Some("Z is undetermined: rule A=>Z|B allows it to be both true and false".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn disabled_rule() -> Result<()> {
+        let mut rules: Vec<Rule> = vec!["r1:A=>B".parse()?, "A=>C".parse()?];
+        let solve = |rules: &[Rule]| {
+            let mut solver = Solver::new(rules, "A").with_default(Truth::Undetermined);
+            solver.solve();
+            (solver.get('B'), solver.unfired_rules())
+        };
+        assert_eq!(solve(&rules), (Truth::True, vec![]));
+        rule::set_enabled(&mut rules, "r1", false)?;
+        assert_eq!(solve(&rules), (Truth::Undetermined, vec![]));
+        rule::set_enabled(&mut rules, "r1", true)?;
+        assert_eq!(solve(&rules), (Truth::True, vec![]));
+        Ok(())
+    }
+
+    #[test]
+    fn max_depth() -> Result<()> {
+        let rules = test_utils::rules(&["C=>D", "B=>C", "A=>B"])?;
+        let config = SolveConfig::builder().max_depth(2).build();
+        let outcome = run(&rules, "A", "BCD", &config);
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('B'), Truth::True),
+                (Query::from('C'), Truth::True),
+                (Query::from('D'), Truth::Undetermined)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cancel() -> Result<()> {
+        // In this order every pass over the rules derives one more symbol of the chain.
+        let rules = test_utils::rules(&["C=>D", "B=>C", "A=>B"])?;
+        let cancel = CancelFlag::new();
+        let mut solver = Solver::new(&rules, "A").with_cancel(Some(cancel.clone()));
+        assert!(solver.forward());
+        cancel.cancel();
+        solver.solve();
+        assert!(solver.cancelled());
+        assert_eq!(
+            "ABCD".chars().map(|c| solver.get(c)).collect::<Vec<_>>(),
+            vec![
+                Truth::True,
+                Truth::True,
+                Truth::Undetermined,
+                Truth::Undetermined
+            ]
+        );
+
+        let config = SolveConfig::builder().cancel(cancel).build();
+        let outcome = run(&rules, "A", "AD", &config);
+        assert!(outcome.cancelled);
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('A'), Truth::True),
+                (Query::from('D'), Truth::Undetermined)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_invalid_mode() {
+        let result = "sideways".parse::<Mode>();
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "Invalid mode: 'sideways'");
+    }
+
+    #[test]
+    fn abduce_single_fact() -> Result<()> {
+        let rules = test_utils::rules(&["A+B=>C", "C=>Z", "D=>E"])?;
+        assert_eq!(abduce(&rules, "A", 'Z'), vec![BTreeSet::from(['B'])]);
+        Ok(())
+    }
+
+    #[test]
+    fn abduce_minimal() -> Result<()> {
+        let rules = test_utils::rules(&["A+B=>Z", "C=>Z", "A=>!D", "D=>Z"])?;
+        assert_eq!(
+            abduce(&rules, "", 'Z'),
+            vec![BTreeSet::from(['C']), BTreeSet::from(['A', 'B'])]
+        );
+        assert_eq!(abduce(&rules, "C", 'Z'), vec![BTreeSet::new()]);
+        assert_eq!(
+            abduce(&rules, "A", 'Z'),
+            vec![BTreeSet::from(['B']), BTreeSet::from(['C'])]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fact() -> Result<()> {
+        let outcome = run(&test_utils::rules(&[])?, "A", "AB", &SolveConfig::default());
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('A'), Truth::True),
+                (Query::from('B'), Truth::False)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn chain() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["B=>C", "A=>B"])?,
+            "A",
+            "ABC",
+            &SolveConfig::default(),
+        );
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('A'), Truth::True),
+                (Query::from('B'), Truth::True),
+                (Query::from('C'), Truth::True)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn closure_levels() -> Result<()> {
+        assert_eq!(
+            closure(&test_utils::rules(&["A=>B", "B=>C"])?, "A"),
+            BTreeMap::from([('A', 0), ('B', 1), ('C', 2)])
+        );
+        // The level doesn't depend on the order the rules fire in, and a rule is as deep as its
+        // deepest premise symbol.
+        assert_eq!(
+            closure(
+                &test_utils::rules(&["B+D=>E", "B=>C", "C=>D", "A=>B", "!F=>G"])?,
+                "A"
+            ),
+            BTreeMap::from([('A', 0), ('B', 1), ('C', 2), ('D', 3), ('E', 4), ('G', 1)])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn derived_facts() -> Result<()> {
+        let rules = test_utils::rules(&["A=>B", "B=>!C", "!D=>E", "F=>G"])?;
+        let mut solver = Solver::new(&rules, "A!F");
+        solver.solve();
+        let facts = solver.derived_facts();
+        assert_eq!(facts, "AB!CE!F");
+        let mut checkpoint = Solver::new(&rules, &facts);
+        checkpoint.solve();
+        assert_eq!(checkpoint.state(), solver.state());
+        assert_eq!(checkpoint.derived_facts(), facts);
+        Ok(())
+    }
+
+    #[test]
+    fn closed_world() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["!B=>C", "A=>B", "B=>A"])?,
+            "",
+            "ABC",
+            &SolveConfig::default(),
+        );
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('A'), Truth::False),
+                (Query::from('B'), Truth::False),
+                (Query::from('C'), Truth::True)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn stop_on_contradiction() -> Result<()> {
+        let rules = test_utils::rules(&["A=>B", "A=>!B", "A=>C"])?;
+        let mut solver = Solver::new(&rules, "A").with_stop_on_contradiction(true);
+        solver.solve();
+        assert_eq!(solver.contradictions.len(), 1);
+        assert_eq!(
+            solver.contradictions[0].to_string(),
+            "Contradiction for 'B': rule 'A=>B' makes it true but rule 'A=>!B' makes it false"
+        );
+        assert_eq!(solver.get('C'), Truth::Undetermined);
+
+        let mut solver = Solver::new(&rules, "A");
+        solver.solve();
+        assert_eq!(solver.get('C'), Truth::True);
+        Ok(())
+    }
+
+    #[test]
+    fn stop_on_contradiction_consistent() -> Result<()> {
+        let rules = test_utils::rules(&["A=>B", "B+!D=>C", "C|E=>F"])?;
+        let mut early = Solver::new(&rules, "A").with_stop_on_contradiction(true);
+        early.solve();
+        let mut batch = Solver::new(&rules, "A");
+        batch.solve();
+        assert!(early.contradictions.is_empty());
+        assert_eq!(early.state(), batch.state());
+        Ok(())
+    }
+
+    #[test]
+    fn default_undetermined() -> Result<()> {
+        let rules = test_utils::rules(&["!B=>C", "A=>D"])?;
+        let config = SolveConfig::builder()
+            .default_value(Truth::Undetermined)
+            .build();
+        let outcome = run(&rules, "A", "ZBCD", &config);
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('Z'), Truth::Undetermined),
+                (Query::from('B'), Truth::Undetermined),
+                (Query::from('C'), Truth::Undetermined),
+                (Query::from('D'), Truth::True)
+            ]
+        );
+        let outcome = run(&rules, "A", "ZBCD", &SolveConfig::default());
+        assert_eq!(
+            outcome.answers,
+            run(&rules, "A", "ZBCD", &SolveConfig::default()).answers
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn and_conclusion() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["A=>B+!C"])?,
+            "A",
+            "BC",
+            &SolveConfig::default(),
+        );
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('B'), Truth::True),
+                (Query::from('C'), Truth::False)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn or_conclusion() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["A=>B|C", "C=>D"])?,
+            "A",
+            "BCD",
+            &SolveConfig::default(),
+        );
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('B'), Truth::Undetermined),
+                (Query::from('C'), Truth::Undetermined),
+                (Query::from('D'), Truth::Undetermined)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn partial_premise() -> Result<()> {
+        let rules = test_utils::rules(&["A|B=>C"])?;
+        let mut solver = Solver::new(&rules, "A");
+        assert!(solver.forward());
+        assert_eq!(solver.get('B'), Truth::Undetermined);
+        assert_eq!(solver.get('C'), Truth::True);
+        Ok(())
+    }
+
+    #[test]
+    fn bi_directional() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["A<=>B"])?,
+            "B",
+            "A",
+            &SolveConfig::default(),
+        );
+        assert_eq!(outcome.answers, vec![(Query::from('A'), Truth::True)]);
+        Ok(())
+    }
+
+    #[test]
+    fn contradiction() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["A=>B", "A=>!B"])?,
+            "A",
+            "B",
+            &SolveConfig::default(),
+        );
+        assert_eq!(
+            outcome.contradictions,
+            vec![Contradiction {
+                symbol: 'B',
+                value: true,
+                first: "rule 'A=>B'".to_string(),
+                second: "rule 'A=>!B'".to_string(),
+            }]
+        );
+        assert_eq!(
+            outcome.contradictions[0].to_string(),
+            "Contradiction for 'B': rule 'A=>B' makes it true but rule 'A=>!B' makes it false"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn contradiction_fact() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["A=>!B"])?,
+            "AB",
+            "B",
+            &SolveConfig::default(),
+        );
+        assert_eq!(outcome.answers, vec![(Query::from('B'), Truth::True)]);
+        assert_eq!(
+            outcome.contradictions[0].to_string(),
+            "Contradiction with initial fact 'B': rule 'A=>!B' makes it false"
+        );
+        assert!(outcome.contradictions[0].is_fact());
+        Ok(())
+    }
+
+    #[test]
+    fn false_fact() -> Result<()> {
+        let rules = test_utils::rules(&["A=>B", "B=>C", "!D=>E"])?;
+        let outcome = run(&rules, "!B", "BCDE", &SolveConfig::default());
+        assert!(outcome.contradictions.is_empty());
+        assert_eq!(
+            outcome.answers.iter().map(|(_, t)| *t).collect::<Vec<_>>(),
+            vec![Truth::False, Truth::False, Truth::False, Truth::True]
+        );
+
+        let outcome = run(&rules, "A!B", "BC", &SolveConfig::default());
+        assert_eq!(
+            outcome.answers,
+            vec![
+                (Query::from('B'), Truth::False),
+                (Query::from('C'), Truth::False)
+            ]
+        );
+        assert_eq!(outcome.contradictions.len(), 1);
+        assert!(outcome.contradictions[0].is_fact());
+        assert_eq!(
+            outcome.contradictions[0].to_string(),
+            "Contradiction with initial fact '!B': rule 'A=>B' makes it true"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn priority_resolves_conflict() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["A=>B", "[10]A=>!B"])?,
+            "A",
+            "B",
+            &SolveConfig::default(),
+        );
+        assert_eq!(outcome.answers, vec![(Query::from('B'), Truth::False)]);
+        assert!(outcome.contradictions.is_empty());
+        assert_eq!(
+            outcome.warnings,
+            vec![Warning::new(
+                WarningKind::RuleOverride,
+                "rule 'A=>!B' overrides rule 'A=>B' for 'B'".to_string()
+            )]
+        );
+
+        let outcome = run(
+            &test_utils::rules(&["[10]A=>B", "A=>!B"])?,
+            "A",
+            "B",
+            &SolveConfig::default(),
+        );
+        assert_eq!(outcome.answers, vec![(Query::from('B'), Truth::True)]);
+        assert!(outcome.contradictions.is_empty());
+        assert_eq!(
+            outcome.warnings,
+            vec![Warning::new(
+                WarningKind::RuleOverride,
+                "rule 'A=>B' overrides rule 'A=>!B' for 'B'".to_string()
+            )]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn specificity_resolves_conflict() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["A+C=>!B", "A=>B"])?,
+            "AC",
+            "B",
+            &SolveConfig::default(),
+        );
+        assert_eq!(outcome.answers, vec![(Query::from('B'), Truth::False)]);
+        assert!(outcome.contradictions.is_empty());
+        assert_eq!(
+            outcome.warnings,
+            vec![Warning::new(
+                WarningKind::RuleOverride,
+                "rule 'A+C=>!B' overrides rule 'A=>B' for 'B'".to_string()
+            )]
+        );
+
+        // The priority decides before the specificity.
+        let outcome = run(
+            &test_utils::rules(&["A+C=>!B", "[1]A=>B"])?,
+            "AC",
+            "B",
+            &SolveConfig::default(),
+        );
+        assert_eq!(outcome.answers, vec![(Query::from('B'), Truth::True)]);
+        Ok(())
+    }
+
+    #[test]
+    fn negated_query() -> Result<()> {
+        let rules = test_utils::rules(&["A=>Z", "B=>Y|Z"])?;
+        let negated = |facts: &str| run(&rules, facts, "!Z", &SolveConfig::default()).answers[0].1;
+        assert_eq!(negated("A"), Truth::False);
+        assert_eq!(negated(""), Truth::True);
+        assert_eq!(negated("B"), Truth::Undetermined);
+        assert_eq!(
+            Query::parse_all("A!ZB"),
+            vec![
+                Query::from('A'),
+                Query {
+                    symbol: 'Z',
+                    negated: true,
+                    open: false,
+                    assumptions: State::default(),
+                },
+                Query::from('B')
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn open_query() -> Result<()> {
+        let rules = test_utils::rules(&["A=>Z", "!Z=>Y"])?;
+        let outcome = run(&rules, "", "Z~ZY~Y~!Z", &SolveConfig::default());
+        assert_eq!(
+            outcome.answers.iter().map(|(_, t)| *t).collect::<Vec<_>>(),
+            vec![
+                Truth::False,
+                Truth::Undetermined,
+                Truth::True,
+                Truth::Undetermined,
+                Truth::Undetermined
+            ]
+        );
+        assert_eq!(
+            Query::parse_all("~!Z")[0],
+            Query {
+                symbol: 'Z',
+                negated: true,
+                open: true,
+                assumptions: State::default(),
+            }
+        );
+        assert_eq!(Query::parse_all("~!Z")[0].to_string(), "~!Z");
+        Ok(())
+    }
+
+    #[test]
+    fn assumptions() -> Result<()> {
+        let rules = test_utils::rules(&["A+B=>Z", "C=>B"])?;
+        let config = SolveConfig::builder()
+            .default_value(Truth::Undetermined)
+            .build();
+        let outcome = run(&rules, "", "Z|A,BZ", &config);
+        assert_eq!(
+            outcome
+                .answers
+                .iter()
+                .map(|(q, t)| (q.to_string(), *t))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Z | A,B".to_string(), Truth::True),
+                ("Z".to_string(), Truth::Undetermined)
+            ]
+        );
+
+        // An assumption replaces a fact on the same symbol.
+        let outcome = run(&rules, "A!C", "Z|CZ", &SolveConfig::default());
+        assert_eq!(
+            outcome.answers.iter().map(|(_, t)| *t).collect::<Vec<_>>(),
+            vec![Truth::True, Truth::False]
+        );
+        assert_eq!(
+            assume("A!C", &Query::parse_all("Z|C,!D")[0].assumptions),
+            "AC!D"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn or_policy() -> Result<()> {
+        let ambiguous = test_utils::rules(&["A=>B|C"])?;
+        let answers = |or_policy| -> (Vec<Truth>, Vec<Warning>) {
+            let config = SolveConfig::builder().or_policy(or_policy).build();
+            let outcome = run(&ambiguous, "A", "BC", &config);
+            (
+                outcome.answers.iter().map(|(_, t)| *t).collect(),
+                outcome.warnings,
+            )
+        };
+        assert_eq!(
+            answers(OrPolicy::Undetermined),
+            (vec![Truth::Undetermined, Truth::Undetermined], vec![])
+        );
+        assert_eq!(
+            answers(OrPolicy::First),
+            (
+                vec![Truth::True, Truth::Undetermined],
+                vec![Warning::new(
+                    WarningKind::UndecidedConclusion,
+                    "rule 'A=>B|C' doesn't decide 'B', assuming it true".to_string()
+                )]
+            )
+        );
+        assert_eq!(
+            answers(OrPolicy::All),
+            (vec![Truth::True, Truth::True], vec![])
+        );
+
+        // A symbol is only assumed true when the conclusion can still hold.
+        let rules = test_utils::rules(&["A=>B^C"])?;
+        let config = SolveConfig::builder().or_policy(OrPolicy::All).build();
+        let outcome = run(&rules, "A", "BC", &config);
+        assert_eq!(
+            outcome.answers.iter().map(|(_, t)| *t).collect::<Vec<_>>(),
+            vec![Truth::True, Truth::False]
+        );
+        assert!(outcome.contradictions.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn error_invalid_or_policy() {
+        let result = "last".parse::<OrPolicy>();
+        assert_eq!(result.unwrap_err().to_string(), "Invalid or policy: 'last'");
+    }
+
+    #[test]
+    fn unfired_rules() -> Result<()> {
+        let rules = test_utils::rules(&["A=>B", "C=>D", "B+D=>E", "F<=>B"])?;
+        let outcome = run(&rules, "A", "E", &SolveConfig::default());
+        assert_eq!(outcome.unfired_rules, vec!["C=>D", "B+D=>E"]);
+        let outcome = run(&rules, "AC", "E", &SolveConfig::default());
+        assert!(outcome.unfired_rules.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn rule_order() -> Result<()> {
+        let rules = test_utils::rules(&["A+B=>C", "C=>D|E", "!F=>G", "D<=>H", "G+C=>I", "B=>!J"])?;
+        let expected = run(&rules, "AB", "CDEFGHIJ", &SolveConfig::default()).answers;
+        for seed in [1, 7] {
+            let mut shuffled = rules.clone();
+            rule::shuffle_rules(&mut shuffled, seed);
+            assert_eq!(
+                run(&shuffled, "AB", "CDEFGHIJ", &SolveConfig::default()).answers,
+                expected
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn proof() -> Result<()> {
+        let proof = prove(&test_utils::rules(&["B=>C", "A=>B"])?, "A", 'C');
+        assert_eq!(
+            proof,
+            Some(ProofNode {
+                symbol: 'C',
+                value: true,
+                justification: Justification::Rule("B=>C".to_string()),
+                children: vec![ProofNode {
+                    symbol: 'B',
+                    value: true,
+                    justification: Justification::Rule("A=>B".to_string()),
+                    children: vec![ProofNode {
+                        symbol: 'A',
+                        value: true,
+                        justification: Justification::Fact,
+                        children: vec![],
+                    }],
+                }],
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn proof_display() -> Result<()> {
+        let proof = prove(&test_utils::rules(&["A+!B=>C"])?, "A", 'C').unwrap();
+        assert_eq!(
+            proof.to_string(),
+            indoc! {"
+                C is true (rule 'A+!B=>C')
+                  A is true (initial fact)
+                  B is false (closed-world assumption)
+            "}
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn run_proofs() -> Result<()> {
+        let outcome = run(
+            &test_utils::rules(&["A=>B"])?,
+            "A",
+            "BB|!A",
+            &SolveConfig::default(),
+        );
+        let queries = Query::parse_all("BB|!A");
+        // Every proof comes from the solver that answered its query.
+        assert!(outcome.proofs[&queries[0]].value);
+        assert!(!outcome.proofs[&queries[1]].value);
+        Ok(())
+    }
+
+    #[test]
+    fn proof_undetermined() -> Result<()> {
+        assert_eq!(prove(&test_utils::rules(&["A=>B|C"])?, "A", 'B'), None);
+        Ok(())
+    }
+
+    #[test]
+    fn all_supports_disjoint() -> Result<()> {
+        let rules = test_utils::rules(&["A=>Z", "B+C=>Z", "D=>E"])?;
+        assert_eq!(
+            all_supports(&rules, "ABCD", 'Z'),
+            vec![BTreeSet::from(['A']), BTreeSet::from(['B', 'C'])]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn all_supports_fact() -> Result<()> {
+        let rules = test_utils::rules(&["A=>Z"])?;
+        assert_eq!(
+            all_supports(&rules, "AZ", 'Z'),
+            vec![BTreeSet::from(['A']), BTreeSet::from(['Z'])]
+        );
+        assert_eq!(all_supports(&rules, "", 'Z'), Vec::<BTreeSet<char>>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn all_supports_without_facts() -> Result<()> {
+        let rules = test_utils::rules(&["!A=>Z"])?;
+        assert_eq!(all_supports(&rules, "A", 'Z'), vec![BTreeSet::new()]);
+        Ok(())
+    }
+
+    #[test]
+    fn next_combination() {
+        let mut indices = vec![0, 1];
+        let mut combinations = vec![indices.clone()];
+        while super::next_combination(&mut indices, 4) {
+            combinations.push(indices.clone());
+        }
+        assert_eq!(
+            combinations,
+            vec![
+                vec![0, 1],
+                vec![0, 2],
+                vec![0, 3],
+                vec![1, 2],
+                vec![1, 3],
+                vec![2, 3]
+            ]
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use crate::*;
+use symbol_table::{internal_symbol, symbol_index, MAX_SYMBOLS};
+use truth::Truth;
+
+use std::collections::HashMap;
+
+// State holds the known values of the symbols as two bitmasks, the bit of the symbol's index (see
+// symbol_table) in `trues` is set when it is known true and in `falses` when it is known false. A
+// symbol is never set in both.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct State {
+    trues: u64,
+    falses: u64,
+}
+
+// Returns the bit of the symbol, or None when it isn't a symbol (see symbol_table).
+fn bit(symbol: char) -> Option<u64> {
+    symbol_index(symbol).map(|index| 1 << index)
+}
+
+// Returns the bit of a symbol that is set. A state can't hold anything but symbols, so setting
+// anything else is a bug in the caller.
+fn set_bit(symbol: char) -> u64 {
+    bit(symbol).unwrap_or_else(|| panic!("Invalid symbol '{}'", symbol))
+}
+
+impl State {
+    pub fn set_true(&mut self, symbol: char) {
+        let bit = set_bit(symbol);
+        self.trues |= bit;
+        self.falses &= !bit;
+    }
+
+    pub fn set_false(&mut self, symbol: char) {
+        let bit = set_bit(symbol);
+        self.falses |= bit;
+        self.trues &= !bit;
+    }
+
+    pub fn set(&mut self, symbol: char, value: bool) {
+        if value {
+            self.set_true(symbol)
+        } else {
+            self.set_false(symbol)
+        }
+    }
+
+    // Returns the value of the symbol, or None when it is unknown. Anything that isn't a symbol is
+    // never known.
+    pub fn get(&self, symbol: char) -> Option<bool> {
+        let bit = bit(symbol)?;
+        if self.trues & bit != 0 {
+            Some(true)
+        } else if self.falses & bit != 0 {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, symbol: char) -> bool {
+        bit(symbol).is_some_and(|bit| (self.trues | self.falses) & bit != 0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trues | self.falses == 0
+    }
+
+    // Iterates over the known symbols with their values in order of index, A to Z first.
+    pub fn iter(&self) -> impl Iterator<Item = (char, bool)> + '_ {
+        (0..MAX_SYMBOLS)
+            .map(internal_symbol)
+            .filter_map(|c| Some((c, self.get(c)?)))
+    }
+
+    // Returns the known values as booleans, as used to evaluate a node.
+    pub fn to_values(&self) -> HashMap<char, bool> {
+        self.iter().collect()
+    }
+
+    // Returns the known values as truths, e.g. for display.
+    pub fn to_map(&self) -> HashMap<char, Truth> {
+        self.iter().map(|(c, b)| (c, Truth::from(b))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_state {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn set_and_get() {
+        let mut state = State::default();
+        state.set_true('A');
+        state.set_false('Z');
+        assert_eq!(state.get('A'), Some(true));
+        assert_eq!(state.get('Z'), Some(false));
+        assert_eq!(state.get('M'), None);
+        assert!(state.contains('Z'));
+        assert!(!state.contains('M'));
+        assert!(!state.is_empty());
+        assert!(State::default().is_empty());
+
+        state.set_false('A');
+        assert_eq!(state.get('A'), Some(false));
+        assert_eq!(
+            state.iter().collect::<Vec<_>>(),
+            vec![('A', false), ('Z', false)]
+        );
+    }
+
+    #[test]
+    fn symbols_past_z() {
+        let mut state = State::default();
+        let last = internal_symbol(MAX_SYMBOLS - 1);
+        state.set_true(last);
+        state.set_false(internal_symbol(26));
+        state.set_true('A');
+        assert_eq!(state.get(last), Some(true));
+        assert_eq!(
+            state.iter().collect::<Vec<_>>(),
+            vec![('A', true), (internal_symbol(26), false), (last, true)]
+        );
+    }
+
+    #[test]
+    fn invalid_symbol() {
+        // An invalid symbol doesn't share the bit of 'A'.
+        let mut state = State::default();
+        state.set_true('A');
+        assert_eq!(state.get('a'), None);
+        assert!(!state.contains('a'));
+        assert_eq!(state.get('?'), None);
+    }
+
+    #[test]
+    fn agrees_with_map() {
+        // The derivation of `A => B`, `B => !C` and `C | D => E` from the fact A, followed by the
+        // closed-world assumption for D and E.
+        let steps = [
+            ('A', true),
+            ('B', true),
+            ('C', false),
+            ('D', false),
+            ('E', false),
+        ];
+        let mut state = State::default();
+        let mut map = HashMap::new();
+        for (symbol, value) in steps {
+            state.set(symbol, value);
+            map.insert(symbol, Truth::from(value));
+            assert_eq!(state.to_map(), map);
+        }
+        assert_eq!(
+            state.to_values(),
+            map.iter()
+                .map(|(c, t)| (*c, *t == Truth::True))
+                .collect::<HashMap<_, _>>()
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::*;
+
+use rule::Rule;
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+// The number of symbols the engine can tell apart, one bit each in a State.
+pub const MAX_SYMBOLS: usize = 64;
+
+// Symbols past Z are represented by chars of the private use area, which no input contains.
+const FIRST_EXTRA_SYMBOL: u32 = 0xE000;
+
+// Returns the internal symbol of an index: A to Z for the first 26, private use chars after that.
+pub fn internal_symbol(index: usize) -> char {
+    assert!(index < MAX_SYMBOLS, "Symbol index out of range: {}", index);
+    match index {
+        0..=25 => (b'A' + index as u8) as char,
+        _ => char::from_u32(FIRST_EXTRA_SYMBOL + index as u32 - 26).expect("Private use char"),
+    }
+}
+
+// Returns the index of an internal symbol, or None when the char isn't one.
+pub fn symbol_index(c: char) -> Option<usize> {
+    if is_identifier(c) {
+        return Some(c as usize - 'A' as usize);
+    }
+    let index = (c as u32).checked_sub(FIRST_EXTRA_SYMBOL)? as usize + 26;
+    (index < MAX_SYMBOLS).then_some(index)
+}
+
+// Returns whether the char is an internal symbol, the identifiers A to Z included.
+pub fn is_symbol(c: char) -> bool {
+    symbol_index(c).is_some()
+}
+
+// SymbolTable assigns an internal symbol to every name of a formula, e.g. `Rain`, so formulas can
+// use more than the 26 single letter identifiers. Names start with an uppercase letter followed by
+// letters, digits or `_`. The first name gets A, the second B and so on, in order of appearance.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the internal symbol of the name, assigning the next one to a new name.
+    pub fn intern(&mut self, name: &str) -> Result<char> {
+        if let Some(index) = self.indices.get(name) {
+            return Ok(internal_symbol(*index));
+        }
+        if self.names.len() == MAX_SYMBOLS {
+            return Err(anyhow!(
+                "Too many symbols: '{}' would be symbol {} of at most {}",
+                name,
+                MAX_SYMBOLS + 1,
+                MAX_SYMBOLS
+            ));
+        }
+        self.indices.insert(name.to_string(), self.names.len());
+        self.names.push(name.to_string());
+        Ok(internal_symbol(self.names.len() - 1))
+    }
+
+    // Returns the internal symbol of a name that is already assigned one.
+    pub fn symbol(&self, name: &str) -> Option<char> {
+        self.indices.get(name).map(|index| internal_symbol(*index))
+    }
+
+    // Returns the name of an internal symbol.
+    pub fn name(&self, symbol: char) -> Option<&str> {
+        self.names.get(symbol_index(symbol)?).map(|n| n.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    // Replaces every name in the text by its internal symbol, e.g. `A + B => C` for
+    // `Rain + Cold => Snow`, so it can be parsed as a rule, fact or query.
+    pub fn translate(&mut self, text: &str) -> Result<String> {
+        let mut result = String::new();
+        let mut name = String::new();
+        for c in text.chars().chain(['\0']) {
+            if c.is_ascii_uppercase() || !name.is_empty() && (c.is_ascii_alphanumeric() || c == '_')
+            {
+                name.push(c);
+                continue;
+            }
+            if !name.is_empty() {
+                result.push(self.intern(&name)?);
+                name.clear();
+            }
+            if c != '\0' {
+                result.push(c);
+            }
+        }
+        Ok(result)
+    }
+
+    // Translates a rule and parses it, see translate.
+    pub fn parse_rule(&mut self, text: &str) -> Result<Rule> {
+        Rule::parse_translated(&self.translate(text)?)
+    }
+
+    // Replaces every internal symbol in the text by its name, the reverse of translate.
+    pub fn display(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| match self.name(c) {
+                Some(name) => name.to_string(),
+                None => c.to_string(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_symbol_table {
+    use super::*;
+    use parser::RuleParser;
+    use solver::Solver;
+    use truth::Truth;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn internal_symbols() {
+        for index in 0..MAX_SYMBOLS {
+            assert_eq!(symbol_index(internal_symbol(index)), Some(index));
+        }
+        assert_eq!(internal_symbol(0), 'A');
+        assert_eq!(internal_symbol(25), 'Z');
+        assert_eq!(symbol_index('a'), None);
+        assert_eq!(symbol_index('!'), None);
+        assert_eq!(symbol_index(char::from_u32(0xE000 + 38).unwrap()), None);
+    }
+
+    #[test]
+    fn translate() -> Result<()> {
+        let mut table = SymbolTable::new();
+        let translated = table.translate("Rain + Cold_2 => Snow | !Rain")?;
+        assert_eq!(translated, "A + B => C | !A");
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.symbol("Snow"), Some('C'));
+        assert_eq!(table.display(&translated), "Rain + Cold_2 => Snow | !Rain");
+        assert_eq!(table.translate("r1: [2] X1 => 1")?, "r1: [2] D => 1");
+        Ok(())
+    }
+
+    #[test]
+    fn solve_thirty_symbols() -> Result<()> {
+        // A chain of 30 symbols, each concluded by the previous one.
+        let mut table = SymbolTable::new();
+        let rules = (0..29)
+            .map(|i| table.parse_rule(&format!("Step{} => Step{}", i, i + 1)))
+            .collect::<Result<Vec<Rule>>>()?;
+        assert_eq!(table.len(), 30);
+        let facts = table.translate("Step0")?;
+        let mut solver = Solver::new(&rules, &facts);
+        solver.solve();
+        for i in 0..30 {
+            let symbol = table.symbol(&format!("Step{}", i)).unwrap();
+            assert_eq!(solver.get(symbol), Truth::True, "Step{}", i);
+        }
+        let last = table.symbol("Step29").unwrap();
+        assert_eq!(table.name(last), Some("Step29"));
+        assert_eq!(
+            table.display(&solver.justification_of(last).unwrap().to_string()),
+            "rule 'Step28=>Step29'"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn internal_symbols_only_when_translated() -> Result<()> {
+        let mut table = SymbolTable::new();
+        let names: Vec<String> = (0..27).map(|i| format!("S{}", i)).collect();
+        let translated = table.translate(&format!("{} => Z", names.join(" + ")))?;
+        assert!(translated.contains('\u{E000}'));
+        assert!(Rule::parse_translated(&translated).is_ok());
+        assert!(RuleParser::new()
+            .with_internal_symbols()
+            .tokenize(&translated)
+            .is_ok());
+        let result = RuleParser::new().tokenize("A => \u{E000}");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unexpected character: \u{E000}"
+        );
+        assert!(translated.parse::<Rule>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn error_too_many_variables() -> Result<()> {
+        // Every symbol a table can assign is valid, but they are too many to enumerate.
+        let mut table = SymbolTable::new();
+        let names: Vec<String> = (0..MAX_SYMBOLS).map(|i| format!("S{}", i)).collect();
+        let expected = "formula has 64 variables; maximum supported is 63";
+        let rule = table.parse_rule(&format!("{} => S63", names[..63].join(" + ")))?;
+        assert_eq!(
+            inference::count_models(&[rule]).unwrap_err().to_string(),
+            expected
+        );
+        let node = RuleParser::new()
+            .with_internal_symbols()
+            .parse_formula(&table.translate(&names.join(" | "))?)?;
+        assert_eq!(node.count_true().unwrap_err().to_string(), expected);
+        assert!(node.classify().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn error_too_many_symbols() -> Result<()> {
+        let mut table = SymbolTable::new();
+        for i in 0..MAX_SYMBOLS {
+            table.intern(&format!("S{}", i))?;
+        }
+        assert_eq!(
+            table.intern("S64").unwrap_err().to_string(),
+            "Too many symbols: 'S64' would be symbol 65 of at most 64"
+        );
+        assert_eq!(table.intern("S0")?, 'A');
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::{fmt, ops, str::FromStr};
+
+// Truth is the value a symbol resolves to. A symbol is undetermined when the rules allow it to be
+// both true and false, e.g. a symbol in an OR conclusion.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Truth {
+    True,
+    False,
+    Undetermined,
+}
+
+impl From<bool> for Truth {
+    fn from(b: bool) -> Self {
+        if b {
+            Truth::True
+        } else {
+            Truth::False
+        }
+    }
+}
+
+// The operators of Kleene's three-valued logic. An operator is only undetermined when the known
+// operands don't decide it, so `true | undetermined` is true but `true + undetermined` is
+// undetermined. Negation is ops::Not, written `!truth`.
+impl Truth {
+    pub fn and(self, other: Truth) -> Truth {
+        match (self, other) {
+            (Truth::False, _) | (_, Truth::False) => Truth::False,
+            (Truth::True, Truth::True) => Truth::True,
+            _ => Truth::Undetermined,
+        }
+    }
+
+    pub fn or(self, other: Truth) -> Truth {
+        match (self, other) {
+            (Truth::True, _) | (_, Truth::True) => Truth::True,
+            (Truth::False, Truth::False) => Truth::False,
+            _ => Truth::Undetermined,
+        }
+    }
+
+    // Xor is undetermined as soon as either operand is.
+    pub fn xor(self, other: Truth) -> Truth {
+        match (self, other) {
+            (Truth::Undetermined, _) | (_, Truth::Undetermined) => Truth::Undetermined,
+            (lhs, rhs) => Truth::from(lhs != rhs),
+        }
+    }
+
+    // Implication holds as `!self | other`.
+    pub fn implies(self, other: Truth) -> Truth {
+        (!self).or(other)
+    }
+
+    // Equivalence is undetermined as soon as either operand is, like xor.
+    pub fn iff(self, other: Truth) -> Truth {
+        !self.xor(other)
+    }
+}
+
+impl ops::Not for Truth {
+    type Output = Truth;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Truth::True => Truth::False,
+            Truth::False => Truth::True,
+            Truth::Undetermined => Truth::Undetermined,
+        }
+    }
+}
+
+impl fmt::Display for Truth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Truth::True => write!(f, "true"),
+            Truth::False => write!(f, "false"),
+            Truth::Undetermined => write!(f, "undetermined"),
+        }
+    }
+}
+
+// BoolFormat selects the words used to render a Truth in the query answers.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum BoolFormat {
+    #[default]
+    Words,
+    Bits,
+    YesNo,
+}
+
+impl BoolFormat {
+    pub fn render(&self, truth: Truth) -> &'static str {
+        match (self, truth) {
+            (BoolFormat::Words, Truth::True) => "true",
+            (BoolFormat::Words, Truth::False) => "false",
+            (BoolFormat::Words, Truth::Undetermined) => "undetermined",
+            (BoolFormat::Bits, Truth::True) => "1",
+            (BoolFormat::Bits, Truth::False) => "0",
+            (BoolFormat::YesNo, Truth::True) => "yes",
+            (BoolFormat::YesNo, Truth::False) => "no",
+            (BoolFormat::Bits | BoolFormat::YesNo, Truth::Undetermined) => "?",
+        }
+    }
+}
+
+impl FromStr for BoolFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "words" => Ok(BoolFormat::Words),
+            "bits" => Ok(BoolFormat::Bits),
+            "yesno" => Ok(BoolFormat::YesNo),
+            _ => Err(anyhow!("Invalid bool format: '{}'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_bool_format {
+    use super::*;
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    fn render_all(format: BoolFormat) -> Vec<&'static str> {
+        [Truth::True, Truth::False, Truth::Undetermined]
+            .into_iter()
+            .map(|t| format.render(t))
+            .collect()
+    }
+
+    #[test]
+    fn words() -> Result<()> {
+        let format: BoolFormat = "words".parse()?;
+        assert_eq!(render_all(format), vec!["true", "false", "undetermined"]);
+        assert_eq!(format.render(Truth::True), Truth::True.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn bits() -> Result<()> {
+        let format: BoolFormat = "bits".parse()?;
+        assert_eq!(render_all(format), vec!["1", "0", "?"]);
+        Ok(())
+    }
+
+    #[test]
+    fn yesno() -> Result<()> {
+        let format: BoolFormat = "yesno".parse()?;
+        assert_eq!(render_all(format), vec!["yes", "no", "?"]);
+        Ok(())
+    }
+
+    #[test]
+    fn error_invalid() {
+        let result = "maybe".parse::<BoolFormat>();
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid bool format: 'maybe'"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_truth {
+    use super::*;
+    use std::ops::Not;
+
+    use pretty_assertions::assert_eq;
+
+    const ALL: [Truth; 3] = [Truth::True, Truth::False, Truth::Undetermined];
+
+    // Applies the operator to every pair of operands, the first operand varying slowest.
+    fn table(op: fn(Truth, Truth) -> Truth) -> Vec<Truth> {
+        ALL.iter()
+            .flat_map(|lhs| ALL.iter().map(move |rhs| op(*lhs, *rhs)))
+            .collect()
+    }
+
+    use Truth::{False as F, True as T, Undetermined as U};
+
+    #[test]
+    fn not() {
+        assert_eq!(ALL.map(Truth::not), [F, T, U]);
+        assert_eq!(ALL.map(|t| !t), [F, T, U]);
+    }
+
+    #[test]
+    fn and() {
+        assert_eq!(table(Truth::and), vec![T, F, U, F, F, F, U, F, U]);
+    }
+
+    #[test]
+    fn or() {
+        assert_eq!(table(Truth::or), vec![T, T, T, T, F, U, T, U, U]);
+    }
+
+    #[test]
+    fn xor() {
+        assert_eq!(table(Truth::xor), vec![F, T, U, T, F, U, U, U, U]);
+    }
+
+    #[test]
+    fn implies() {
+        assert_eq!(table(Truth::implies), vec![T, F, U, T, T, T, T, U, U]);
+    }
+
+    #[test]
+    fn iff() {
+        assert_eq!(table(Truth::iff), vec![T, F, U, F, T, U, U, U, U]);
+    }
+
+    #[test]
+    fn serde() {
+        for (truth, json) in [(T, "\"true\""), (F, "\"false\""), (U, "\"undetermined\"")] {
+            assert_eq!(serde_json::to_string(&truth).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Truth>(json).unwrap(), truth);
+        }
+    }
+}
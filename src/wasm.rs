@@ -0,0 +1,10 @@
+use crate::*;
+
+use wasm_bindgen::prelude::*;
+
+// Solves the full text of an input file and returns the answers as JSON, see `json::evaluate`.
+// Solving is sequential, so this doesn't need threads in the browser.
+#[wasm_bindgen]
+pub fn evaluate(input: &str) -> String {
+    json::evaluate(input)
+}
@@ -0,0 +1,108 @@
+use crate::*;
+use cancel::CancelFlag;
+
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+// How often the cancel flag is checked while waiting for a change.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Calls `on_change` every time the file changes until the flag is cancelled. The directory of the
+// file is watched rather than the file itself, so the file is still watched after an editor
+// replaces it. The events of a single save are handled as one change.
+pub fn watch(path: &Path, cancel: &CancelFlag, mut on_change: impl FnMut()) -> Result<()> {
+    let path = path
+        .canonicalize()
+        .context(format!("Unable to watch file: '{}'", path.display()))?;
+    let directory = path.parent().unwrap_or(&path);
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).context("Unable to create watcher")?;
+    watcher
+        .watch(directory, RecursiveMode::NonRecursive)
+        .context(format!("Unable to watch file: '{}'", path.display()))?;
+    while !cancel.is_cancelled() {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(event) => {
+                let mut changed = concerns(&event?, &path);
+                while let Ok(event) = receiver.try_recv() {
+                    changed |= concerns(&event?, &path);
+                }
+                if changed {
+                    on_change();
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    Ok(())
+}
+
+// Returns whether the event changes the contents of the file.
+fn concerns(event: &Event, path: &Path) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event
+            .paths
+            .iter()
+            .any(|p| p.file_name() == path.file_name() && p.parent() == path.parent())
+}
+
+#[cfg(test)]
+mod tests_watch {
+    use super::*;
+    use std::{env, fs, thread};
+
+    use anyhow::Result;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn rerun_on_change() -> Result<()> {
+        let directory = env::temp_dir().join(format!("expert_system_watch_{}", std::process::id()));
+        fs::create_dir_all(&directory)?;
+        let path = directory.join("input.txt");
+        fs::write(&path, "A => B\n=A\n?B\n")?;
+
+        let cancel = CancelFlag::new();
+        let writer = {
+            let (path, cancel) = (path.clone(), cancel.clone());
+            thread::spawn(move || {
+                // Keeps touching the file until the change is seen, the watcher may not be set up
+                // yet for the first write.
+                for _ in 0..50 {
+                    if cancel.is_cancelled() {
+                        return;
+                    }
+                    thread::sleep(POLL_INTERVAL);
+                    fs::write(&path, "A => B\n=A\n?A\n").unwrap();
+                }
+                cancel.cancel();
+            })
+        };
+        let mut runs = 0;
+        watch(&path, &cancel, || {
+            runs += 1;
+            cancel.cancel();
+        })?;
+        writer.join().unwrap();
+        fs::remove_dir_all(&directory)?;
+        assert_eq!(runs, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn error_missing_file() {
+        let result = watch(
+            Path::new("tests/input/watch/missing.txt"),
+            &CancelFlag::new(),
+            || {},
+        );
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unable to watch file: 'tests/input/watch/missing.txt'"
+        );
+    }
+}
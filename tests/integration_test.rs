@@ -1,5 +1,6 @@
 extern crate expert_system;
 use expert_system::USAGE;
+use indoc::indoc;
 
 mod test_utils;
 
@@ -77,3 +78,632 @@ fn error_invalid_queries() {
     let input_file = test_utils::input_file_path("integration_test/invalid_queries.txt");
     run_cmd!(input_file.display().to_string()).failure();
 }
+
+#[test]
+fn proof() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let output = run_cmd!("--proof".to_string(), input_file.display().to_string())
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains(indoc! {"
+        C is true (rule 'B=>C')
+          B is true (rule 'A=>B')
+            A is true (initial fact)
+    "}));
+}
+
+#[test]
+fn why() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let output = run_cmd!("--why".to_string(), input_file.display().to_string())
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("C is true (via B=>C)\n"));
+}
+
+#[test]
+fn why_not() {
+    let input_file = test_utils::input_file_path("integration_test/exit_code.txt");
+    let output = run_cmd!("--why".to_string(), input_file.display().to_string())
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("C is false\n  C is false: no rule concludes it\n"));
+}
+
+#[test]
+fn explain_undetermined() {
+    let input_file = test_utils::input_file_path("integration_test/undetermined.txt");
+    let output = run_cmd!(
+        "--explain-undetermined".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("Z is undetermined: rule A+B=>Z has undetermined premise symbol B\n"));
+    assert!(stdout.contains("D is undetermined: no rule concludes it\n"));
+}
+
+#[test]
+fn error_wide_rule_without_confirmation() {
+    let input_file = test_utils::input_file_path("integration_test/wide.txt");
+    let output = run_cmd!(input_file.display().to_string())
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(output).unwrap();
+    assert!(stderr.contains("Refusing to evaluate 67108864 rows without confirmation"));
+}
+
+#[test]
+fn verbose() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let output = run_cmd!("--verbose".to_string(), input_file.display().to_string())
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("rule 'A=>B': priority 0, specificity 1\n"));
+}
+
+#[test]
+fn eval() {
+    let mut cmd = assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    let output = cmd
+        .arg("--eval")
+        .write_stdin("A | !A\n\nA + !A\nA => B\n")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        indoc! {"
+            A|!A: tautology, 2 of 2 rows true
+            A+!A: contradiction, 0 of 2 rows true
+            A=>B: contingent, 3 of 4 rows true
+        "}
+    );
+}
+
+#[test]
+fn json_warnings() {
+    let input_file = test_utils::input_file_path("integration_test/duplicate_rule.txt");
+    let output = run_cmd!("--json".to_string(), input_file.display().to_string())
+        .success()
+        .get_output()
+        .clone();
+    assert!(output.stderr.is_empty());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let warnings = report["warnings"].as_array().unwrap();
+    assert!(warnings
+        .iter()
+        .any(|w| w["kind"] == "duplicate-rule"
+            && w["message"] == "rule 'A=>B' is given more than once"));
+    assert_eq!(report["answers"][0]["value"], "true");
+}
+
+#[test]
+fn json_or_policy() {
+    let input_file = test_utils::input_file_path("integration_test/undetermined.txt");
+    let output = run_cmd!(
+        "--json".to_string(),
+        "--or-policy".to_string(),
+        "first".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    // Only B is concluded from A => B | C, so A + B => Z concludes Z.
+    assert_eq!(report["answers"][0]["query"], "Z");
+    assert_eq!(report["answers"][0]["value"], "true");
+    assert_eq!(report["warnings"][0]["kind"], "undecided-conclusion");
+    assert_eq!(
+        report["warnings"][0]["message"],
+        "rule 'A=>B|C' doesn't decide 'B', assuming it true"
+    );
+}
+
+#[test]
+fn relevant() {
+    let input_file = test_utils::input_file_path("integration_test/relevant.txt");
+    let output = run_cmd!("--relevant".to_string(), input_file.display().to_string())
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("| A | B | = |"));
+    assert!(stdout.contains("| B | Z | = |"));
+    assert!(!stdout.contains("| C | D | = |"));
+}
+
+#[test]
+fn bool_format() {
+    let input_file = test_utils::input_file_path("integration_test/bool_format.txt");
+    for (format, expected) in [
+        ("words", "B is true\nC is undetermined\nE is false\n"),
+        ("bits", "B is 1\nC is ?\nE is 0\n"),
+        ("yesno", "B is yes\nC is ?\nE is no\n"),
+    ] {
+        let output = run_cmd!(
+            "--bool-format".to_string(),
+            format.to_string(),
+            input_file.display().to_string()
+        )
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+        assert!(String::from_utf8(output).unwrap().ends_with(expected));
+    }
+}
+
+#[test]
+fn tokens() {
+    run_cmd!("--tokens", "A+B=>C")
+        .success()
+        .stdout("Identifier(A) Operator(+) Identifier(B) Implicator Identifier(C)\n");
+}
+
+#[test]
+fn env_facts() {
+    let input_file = test_utils::input_file_path("integration_test/env_facts.txt");
+    let output = Command::cargo_bin(env!("CARGO_PKG_NAME"))
+        .unwrap()
+        .arg(&input_file)
+        .env("EXPERT_SYSTEM_FACTS", "A")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(String::from_utf8(output).unwrap().ends_with("Z is true\n"));
+}
+
+#[test]
+fn env_facts_allow_unset() {
+    let input_file = test_utils::input_file_path("integration_test/env_facts.txt");
+    let output = run_cmd!(
+        "--allow-unset".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    assert!(String::from_utf8(output).unwrap().ends_with("Z is false\n"));
+}
+
+#[test]
+fn error_env_facts_unset() {
+    let input_file = test_utils::input_file_path("integration_test/env_facts.txt");
+    run_cmd!(input_file.display().to_string()).failure();
+}
+
+#[test]
+fn summary() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let output = run_cmd!(input_file.display().to_string())
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.starts_with("Loaded 2 rules, 1 fact, 1 query over 3 symbols\nRules:\n"));
+}
+
+#[test]
+fn quiet() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let output = run_cmd!("--quiet".to_string(), input_file.display().to_string())
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(!stdout.contains("Rules:"));
+    assert!(!stdout.contains("Loaded"));
+    assert!(stdout.ends_with("C is true\n"));
+}
+
+#[test]
+fn no_tables() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    run_cmd!(
+        "--quiet".to_string(),
+        "--no-tables".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stdout("C is true\n");
+}
+
+#[test]
+fn table_query() {
+    let input_file = test_utils::input_file_path("integration_test/table_query.txt");
+    run_cmd!(
+        "--quiet".to_string(),
+        "--no-tables".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stdout(indoc! {"
+        A+B
+        | A | B | = |
+        |---|---|---|
+        | 0 | 0 | 0 |
+        | 0 | 1 | 0 |
+        | 1 | 0 | 0 |
+        | 1 | 1 | 1 |
+
+    "});
+}
+
+#[test]
+fn checkpoint() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let checkpoint = std::env::temp_dir().join("expert_system_checkpoint.txt");
+    run_cmd!(
+        "--quiet".to_string(),
+        "--no-tables".to_string(),
+        "--checkpoint".to_string(),
+        checkpoint.display().to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stdout("C is true\n");
+    assert_eq!(
+        std::fs::read_to_string(&checkpoint).unwrap(),
+        "A => B\nB => C\n\n=ABC\n\n?C\n"
+    );
+    run_cmd!(
+        "--quiet".to_string(),
+        "--no-tables".to_string(),
+        "--why".to_string(),
+        checkpoint.display().to_string()
+    )
+    .success()
+    .stdout("C is true (initial fact)\n");
+    std::fs::remove_file(checkpoint).unwrap();
+}
+
+#[test]
+fn dialect() {
+    let input_file = test_utils::input_file_path("integration_test/c_style.txt");
+    run_cmd!(
+        "--quiet".to_string(),
+        "--no-tables".to_string(),
+        "--dialect".to_string(),
+        "c-style".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stdout("C is true\nD is false\n");
+    run_cmd!(input_file.display().to_string()).failure();
+}
+
+#[test]
+fn fmt() {
+    let input_file = test_utils::input_file_path("integration_test/spacing.txt");
+    run_cmd!("--fmt".to_string(), input_file.display().to_string())
+        .success()
+        .stdout("A => Z\n\n=A\n\n?Z\n");
+}
+
+#[test]
+fn sort_rules_fmt() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    run_cmd!(
+        "--sort-rules".to_string(),
+        "--fmt".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stdout("A => B\nB => C\n\n=A\n\n?C\n");
+    let input_file = test_utils::input_file_path("integration_test/relevant.txt");
+    let output = run_cmd!(
+        "--sort-rules".to_string(),
+        "--fmt".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let conclusions: Vec<&str> = stdout
+        .lines()
+        .filter_map(|l| l.split_once(" => "))
+        .map(|(_, conclusion)| conclusion)
+        .collect();
+    let mut sorted = conclusions.clone();
+    sorted.sort_unstable();
+    assert_eq!(conclusions, sorted);
+}
+
+#[test]
+fn count_models() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    run_cmd!(
+        "--quiet".to_string(),
+        "--count-models".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stdout("4 models\n");
+}
+
+#[test]
+fn consistency() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    run_cmd!(
+        "--quiet".to_string(),
+        "--consistency".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stdout("consistent\n");
+    let input_file = test_utils::input_file_path("integration_test/contradiction.txt");
+    run_cmd!(
+        "--quiet".to_string(),
+        "--consistency".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stdout("Contradiction for 'C': rule 'B=>C' makes it true but rule 'E=>!C' makes it false\n");
+}
+
+#[test]
+fn max_threads() {
+    let input_file = test_utils::input_file_path("integration_test/example_input.txt");
+    let run = |args: &[&str]| {
+        let mut args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        args.push(input_file.display().to_string());
+        let output = _run_cmd!(args).success().get_output().stdout.clone();
+        String::from_utf8(output).unwrap()
+    };
+    // The truth tables are printed in hash map order, so only the answers are compared.
+    let sequential = run(&["--max-threads", "1"]);
+    let answers = |stdout: &str| -> Vec<String> {
+        stdout
+            .lines()
+            .filter(|l| l.contains(" is "))
+            .map(|l| l.to_string())
+            .collect()
+    };
+    assert_eq!(
+        answers(&sequential),
+        ["G is true", "V is undetermined", "X is undetermined"]
+    );
+    assert_eq!(answers(&run(&[])), answers(&sequential));
+}
+
+#[test]
+fn facts_file() {
+    let input_file = test_utils::input_file_path("integration_test/rules_only.txt");
+    let facts_file = test_utils::input_file_path("integration_test/scenario.txt");
+    let output = run_cmd!(
+        input_file.display().to_string(),
+        "--facts".to_string(),
+        facts_file.display().to_string()
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("Facts: AB\n"));
+    assert!(stdout.ends_with("Z is true\nY is true\n"));
+}
+
+#[test]
+fn facts_file_duplicate() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let facts_file = test_utils::input_file_path("integration_test/scenario.txt");
+    run_cmd!(
+        input_file.display().to_string(),
+        "--facts".to_string(),
+        facts_file.display().to_string()
+    )
+    .success()
+    .stderr("warning: fact 'A' is given in both the input file and the facts file\n");
+}
+
+#[test]
+fn abduce() {
+    let input_file = test_utils::input_file_path("integration_test/abduce.txt");
+    let output = run_cmd!(
+        "--abduce".to_string(),
+        "Z".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.ends_with(indoc! {"
+        Z is false
+        Z is true when assuming B
+        Z is true when assuming D
+    "}));
+}
+
+#[test]
+fn explain_contradiction() {
+    let input_file = test_utils::input_file_path("integration_test/contradiction.txt");
+    run_cmd!(
+        "--explain-contradiction".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stderr(indoc! {"
+        error: Contradiction for 'C': rule 'B=>C' makes it true but rule 'E=>!C' makes it false
+          C is true (rule 'B=>C')
+            B is true (rule 'A=>B')
+              A is true (initial fact)
+          C is false (rule 'E=>!C')
+            E is true (rule 'D=>E')
+              D is true (initial fact)
+    "});
+}
+
+#[test]
+fn stats() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let output = run_cmd!("--stats".to_string(), input_file.display().to_string())
+        .success()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(output).unwrap();
+    // The timings of both rules, then their complexity.
+    assert_eq!(stderr.lines().count(), 4);
+    assert!(stderr.lines().all(|l| l.starts_with("stats: rule '")));
+    assert!(stderr.contains("stats: rule 'A=>B' has depth 2 and 3 nodes\n"));
+}
+
+#[test]
+fn exit_code() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    run_cmd!("--exit-code".to_string(), input_file.display().to_string()).code(0);
+    let input_file = test_utils::input_file_path("integration_test/exit_code.txt");
+    run_cmd!("--exit-code".to_string(), input_file.display().to_string()).code(1);
+    let input_file = test_utils::input_file_path("integration_test/undetermined.txt");
+    run_cmd!("--exit-code".to_string(), input_file.display().to_string()).code(2);
+    // Without the option every query answered is a success.
+    run_cmd!(input_file.display().to_string()).code(0);
+}
+
+#[test]
+fn strict() {
+    let input_file = test_utils::input_file_path("integration_test/whitespace_facts.txt");
+    run_cmd!("--strict".to_string(), input_file.display().to_string())
+        .success()
+        .stderr("warning: empty facts line on line 3\n");
+    run_cmd!(input_file.display().to_string())
+        .success()
+        .stderr("");
+}
+
+#[test]
+fn conclusions() {
+    let input_file = test_utils::input_file_path("integration_test/undetermined.txt");
+    run_cmd!(
+        "--quiet".to_string(),
+        "--conclusions".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stdout(indoc! {"
+        B is concluded by 'A=>B|C'
+        C is concluded by 'A=>B|C'
+        Z is concluded by 'A+B=>Z'
+    "});
+}
+
+#[test]
+fn closure() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    run_cmd!(
+        "--quiet".to_string(),
+        "--closure".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stdout("A:0, B:1, C:2\n");
+}
+
+#[test]
+fn as_equivalence() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let output = run_cmd!(
+        "--as-equivalence".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.contains("| 0 | 1 | 0 |\n"));
+    assert!(!stdout.contains("| 0 | 1 | 1 |\n"));
+    assert!(stdout.contains("C is true\n"));
+}
+
+#[test]
+fn query_option() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let output = run_cmd!(
+        "--query".to_string(),
+        "A".to_string(),
+        "--query".to_string(),
+        "B".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.ends_with("A is true\nB is true\n"));
+    assert!(!stdout.contains("C is"));
+}
+
+#[test]
+fn query_option_strict() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    run_cmd!(
+        "--strict".to_string(),
+        "--query".to_string(),
+        "Q".to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .stderr("warning: query 'Q' is about a symbol no rule or fact mentions\n");
+    run_cmd!(
+        "--query".to_string(),
+        "q".to_string(),
+        input_file.display().to_string()
+    )
+    .failure();
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn profile() {
+    let input_file = test_utils::input_file_path("integration_test/chain.txt");
+    let output = std::env::temp_dir().join("expert_system_profile.svg");
+    let stdout = run_cmd!(
+        "--quiet".to_string(),
+        "--profile".to_string(),
+        output.display().to_string(),
+        input_file.display().to_string()
+    )
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    assert!(String::from_utf8(stdout).unwrap().ends_with("C is true\n"));
+    // The run is too short to be sampled, only check the profile is written.
+    assert!(output.exists());
+    std::fs::remove_file(output).unwrap();
+}
@@ -1,4 +1,7 @@
 #![allow(dead_code)]
+use expert_system::rule::Rule;
+
+use anyhow::Result;
 use std::path::{Path, PathBuf};
 
 const INPUT_FILES_LOCATION: &str = "tests/input";
@@ -10,3 +13,8 @@ pub fn crate_input_file_path(file_name: impl AsRef<Path>) -> PathBuf {
 pub fn input_file_path(file_name: impl AsRef<Path>) -> PathBuf {
     PathBuf::from(INPUT_FILES_LOCATION).join(file_name)
 }
+
+// Parses every line as a rule in the default implicator style.
+pub fn rules(lines: &[&str]) -> Result<Vec<Rule>> {
+    lines.iter().map(|l| l.parse()).collect()
+}